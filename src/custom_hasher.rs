@@ -0,0 +1,86 @@
+use crate::{
+    type_fingerprint, AlignedBuffer, FrozenHashMap, HashMap, LoadError, Memory, TableLayout,
+};
+use core::{alloc::Layout, mem::ManuallyDrop};
+
+impl<S: Clone> FrozenHashMap<'static, S> {
+    /// Like `construct`, but for a map with any hasher, e.g. `BuildHasherDefault`.
+    /// The hasher is cloned, and the frozen map is generic over its type.
+    pub fn construct_with_hasher<K, V>(hashmap: &std::collections::HashMap<K, V, S>) -> Self {
+        assert_eq!(
+            std::mem::size_of::<HashMap<S>>(),
+            std::mem::size_of::<std::collections::HashMap<K, V, S>>()
+        );
+        // a bitwise copy, which must not drop the hasher it shares with `hashmap`
+        let mirror = ManuallyDrop::new(unsafe {
+            std::ptr::read(
+                hashmap as *const std::collections::HashMap<K, V, S> as *const HashMap<S>,
+            )
+        });
+        let table_layout = TableLayout::new(Layout::new::<(K, V)>());
+        let memory = match mirror.table.table.allocation(&table_layout) {
+            Some((location, layout)) => Memory::Owned(AlignedBuffer::new(
+                unsafe { core::slice::from_raw_parts(location, layout.size()) },
+                table_layout.ctrl_align,
+            )),
+            None => Memory::default(),
+        };
+        Self {
+            table_layout,
+            hashmap: HashMap {
+                hash_builder: hashmap.hasher().clone(),
+                table: mirror.table.clone(),
+            },
+            memory,
+            type_fingerprint: Some(type_fingerprint::<K, V>()),
+        }
+    }
+}
+
+impl<'a, S> FrozenHashMap<'a, S> {
+    /// Like `reconstruct`, for a map frozen with `construct_with_hasher`.
+    /// None if `(K, V)` does not fit the buckets, if
+    /// `std::collections::HashMap<K, V, S>` does not have the size of the frozen
+    /// map, or if the memory does not match the table.
+    pub fn reconstruct_with<K, V>(&mut self) -> Option<&std::collections::HashMap<K, V, S>> {
+        self.check_bucket_type::<K, V>().ok()?;
+        if std::mem::size_of::<HashMap<S>>()
+            != std::mem::size_of::<std::collections::HashMap<K, V, S>>()
+        {
+            return None;
+        }
+        self.fix_ctrl()?;
+        Some(unsafe {
+            &*(&self.hashmap as *const HashMap<S> as *const std::collections::HashMap<K, V, S>)
+        })
+    }
+
+    /// Like `store`, but stores the bytes of any hasher, prefixed by their length.
+    /// A zero-sized hasher like `BuildHasherDefault` takes no bytes.
+    ///
+    /// # Safety
+    ///
+    /// `S` must be plain data without padding, e.g. `BuildHasherDefault` or a
+    /// struct of integers, as every byte of it is read.
+    pub unsafe fn store_with_hasher(&self) -> Vec<u8> {
+        let hasher = unsafe {
+            core::slice::from_raw_parts(
+                &self.hashmap.hash_builder as *const S as *const u8,
+                std::mem::size_of::<S>(),
+            )
+        };
+        self.store_parts(hasher)
+    }
+
+    /// Reverse of `store_with_hasher`. Fails if the stored hasher does not have the
+    /// size of `S`, or for the same reasons as `load`.
+    ///
+    /// # Safety
+    ///
+    /// The hasher is copied byte by byte from `bytes`, so every bit pattern of
+    /// that size must be a valid `S`: plain data, without pointers, references
+    /// or a `Drop` impl.
+    pub unsafe fn load_with_hasher(bytes: &'a [u8]) -> Result<Self, LoadError> {
+        Self::load_parts(bytes, |header| unsafe { header.hasher::<S>() })
+    }
+}
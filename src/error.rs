@@ -0,0 +1,208 @@
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstructError {
+    /// The map's hasher is not `std::collections::hash_map::RandomState`
+    UnsupportedHasher(&'static str),
+}
+
+impl Display for ConstructError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedHasher(name) => write!(f, "Unsupported hasher `{name}`"),
+        }
+    }
+}
+
+impl std::error::Error for ConstructError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeMismatch {
+    /// `(K, V)` is not the type the map was constructed with
+    Fingerprint { expected: u64, found: u64 },
+    /// `(K, V)` does not have the size of a bucket
+    Size { expected: usize, found: usize },
+    /// `(K, V)` needs a larger alignment than the table provides
+    Align { max: usize, found: usize },
+    /// `std::collections::HashMap<K, V>` does not have the size of the frozen map
+    MapSize { expected: usize, found: usize },
+}
+
+impl Display for TypeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fingerprint { expected, found } => write!(
+                f,
+                "Type fingerprint mismatch: expected {expected:#018x}, found {found:#018x}"
+            ),
+            Self::Size { expected, found } => write!(
+                f,
+                "Bucket size mismatch: expected {expected} bytes, found {found}"
+            ),
+            Self::Align { max, found } => {
+                write!(f, "Alignment {found} exceeds the table alignment {max}")
+            }
+            Self::MapSize { expected, found } => write!(
+                f,
+                "HashMap size mismatch: expected {expected} bytes, found {found}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TypeMismatch {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    /// The blob does not start with `MAGIC`
+    BadMagic,
+    /// The blob was stored in a different `FORMAT_VERSION`
+    UnsupportedVersion(u32),
+    /// The blob ends before the header or `memory` does
+    TruncatedBlob,
+    /// There are bytes after the end of `memory`
+    TrailingBytes,
+    /// `ctrl_align` is not a power of two, or is less than `Group::WIDTH`
+    InvalidTableLayout,
+    /// `bucket_mask + 1` is not a power of two
+    NonPowerOfTwoBuckets,
+    /// `items` is larger than the number of buckets
+    ItemsExceedBuckets,
+    /// The length of `memory` is not what the table layout requires
+    MemorySizeMismatch { expected: usize, found: usize },
+    /// The number of full control bytes is not `items`
+    ItemsMismatch { items: usize, full: usize },
+    /// The stored memory length does not fit in a `usize`
+    MemoryTooLarge(u64),
+    /// The trailing control bytes do not mirror the leading ones
+    CorruptCtrlMirror,
+    /// The stored hash_builder does not have the size of the hasher type
+    HasherSizeMismatch { expected: usize, found: usize },
+    /// The stored hash_builder is longer than `MAX_HASHER_LEN`
+    HasherTooLarge(u64),
+    /// The endianness byte of the blob is neither little nor big
+    InvalidEndianness(u8),
+    /// The blob was stored on a platform this one cannot read, e.g. with a
+    /// different pointer width or `Group::WIDTH`
+    PlatformMismatch {
+        what: &'static str,
+        expected: u8,
+        found: u8,
+    },
+    /// The blob was stored by a newer version of this crate
+    NewerProducer {
+        producer: [u8; 3],
+        consumer: [u8; 3],
+    },
+    /// The blob was stored on a platform with a different `abi_signature`
+    AbiMismatch { expected: u64, found: u64 },
+    /// `memory` in the blob is not aligned to `ctrl_align`, so it cannot be borrowed
+    MisalignedMemory { align: usize },
+    /// The blob does not match the checksum stored by `store_with_checksum`
+    ChecksumMismatch { expected: u32, found: u32 },
+    /// Reading the blob failed
+    Io(std::io::ErrorKind),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::BadMagic => write!(f, "Not a frozen hashmap"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Unsupported format version {version}")
+            }
+            Self::TruncatedBlob => write!(f, "Blob is truncated"),
+            Self::TrailingBytes => write!(f, "Blob has trailing bytes"),
+            Self::InvalidTableLayout => write!(f, "Invalid table layout"),
+            Self::NonPowerOfTwoBuckets => write!(f, "Number of buckets is not a power of two"),
+            Self::ItemsExceedBuckets => write!(f, "More items than buckets"),
+            Self::MemorySizeMismatch { expected, found } => write!(
+                f,
+                "Memory size mismatch: expected {expected} bytes, found {found}"
+            ),
+            Self::ItemsMismatch { items, full } => {
+                write!(f, "Table has {items} items but {full} full buckets")
+            }
+            Self::CorruptCtrlMirror => write!(f, "Trailing control bytes are not a mirror"),
+            Self::MemoryTooLarge(len) => {
+                write!(f, "Memory of {len} bytes is too large for this platform")
+            }
+            Self::HasherSizeMismatch { expected, found } => write!(
+                f,
+                "Hasher size mismatch: expected {expected} bytes, found {found}"
+            ),
+            Self::HasherTooLarge(len) => {
+                write!(f, "Hasher of {len} bytes is too large")
+            }
+            Self::InvalidEndianness(byte) => write!(f, "Invalid endianness {byte}"),
+            Self::PlatformMismatch {
+                what,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Platform mismatch: expected {what} {expected}, found {found}"
+            ),
+            Self::NewerProducer { producer, consumer } => write!(
+                f,
+                "Blob was stored by version {}.{}.{}, newer than {}.{}.{}",
+                producer[0], producer[1], producer[2], consumer[0], consumer[1], consumer[2]
+            ),
+            Self::AbiMismatch { expected, found } => write!(
+                f,
+                "ABI signature mismatch: expected {expected:#018x}, found {found:#018x}"
+            ),
+            Self::MisalignedMemory { align } => {
+                write!(f, "Memory is not aligned to {align} bytes")
+            }
+            Self::ChecksumMismatch { expected, found } => write!(
+                f,
+                "Checksum mismatch: expected {expected:#010x}, found {found:#010x}"
+            ),
+            Self::Io(kind) => write!(f, "I/O error: {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractError {
+    /// `ctrl` of the map struct is null, so it is probably not a map
+    NullCtrl,
+    /// `ctrl_align` of the table layout is not a power of two
+    InvalidTableLayout,
+    /// `bucket_mask + 1` is not a power of two
+    NonPowerOfTwoBuckets,
+    /// `items` is larger than the number of buckets
+    ItemsExceedBuckets,
+    /// The table allocation would start before address 0
+    AddressOverflow,
+    /// The table allocation is larger than `MAX_EXTRACT_LEN`
+    TableTooLarge(usize),
+    /// The reader passed to `from_parts` returned nothing, or fewer bytes than asked
+    ReadFailed { addr: u64, len: usize },
+    /// Reading the memory failed
+    Io(std::io::ErrorKind),
+}
+
+impl Display for ExtractError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NullCtrl => write!(f, "Control bytes pointer is null"),
+            Self::InvalidTableLayout => write!(f, "Invalid table layout"),
+            Self::NonPowerOfTwoBuckets => write!(f, "Number of buckets is not a power of two"),
+            Self::ItemsExceedBuckets => write!(f, "More items than buckets"),
+            Self::AddressOverflow => write!(f, "Table allocation address overflows"),
+            Self::TableTooLarge(len) => {
+                write!(f, "Table allocation of {len} bytes is too large")
+            }
+            Self::ReadFailed { addr, len } => {
+                write!(f, "Failed to read {len} bytes at {addr:#x}")
+            }
+            Self::Io(kind) => write!(f, "I/O error: {kind}"),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
@@ -0,0 +1,143 @@
+use crate::{
+    AlignedBuffer, ExtractError, FrozenHashMap, HashMap, Memory, RandomState, RawTable,
+    RawTableInner, TableLayout,
+};
+use core::{mem::MaybeUninit, ptr::NonNull};
+use std::io;
+
+/// Reads the memory of another process, e.g. from a coredump or via `ptrace`
+pub trait MemoryReader {
+    /// Fills `buf` with the bytes at `addr` in the address space being read
+    fn read_at(&self, addr: u64, buf: &mut [u8]) -> io::Result<()>;
+}
+
+/// Upper bound on the size of a table allocation read by `from_parts`, so a
+/// corrupt `bucket_mask` is rejected before anything is allocated for it
+pub const MAX_EXTRACT_LEN: usize = u32::MAX as usize;
+
+impl FrozenHashMap<'static, RandomState> {
+    /// Extracts the `std::collections::HashMap` at `map_addr` in the memory behind
+    /// `reader`: reads the map struct, then the table through `from_parts`.
+    /// `table_layout` must be the one of `(K, V)` of the map, e.g.
+    /// `TableLayout::new(Layout::new::<(K, V)>())`.
+    pub fn from_memory_reader<R: MemoryReader>(
+        reader: &R,
+        map_addr: u64,
+        table_layout: TableLayout,
+    ) -> Result<Self, ExtractError> {
+        let mut hashmap = MaybeUninit::<HashMap<RandomState>>::zeroed();
+        let bytes = unsafe {
+            core::slice::from_raw_parts_mut(
+                hashmap.as_mut_ptr() as *mut u8,
+                std::mem::size_of::<HashMap<RandomState>>(),
+            )
+        };
+        reader
+            .read_at(map_addr, bytes)
+            .map_err(|err| ExtractError::Io(err.kind()))?;
+        // `ctrl` is a `NonNull`, so check it before assuming the struct is valid
+        let ctrl = unsafe {
+            core::ptr::addr_of!((*hashmap.as_ptr()).table.table.ctrl)
+                .cast::<usize>()
+                .read()
+        };
+        if ctrl == 0 {
+            return Err(ExtractError::NullCtrl);
+        }
+        let HashMap {
+            hash_builder,
+            table: RawTable { table },
+        } = unsafe { hashmap.assume_init() };
+
+        let mut error = None;
+        let frozen = Self::from_parts(
+            table_layout,
+            hash_builder,
+            table.bucket_mask,
+            table.items,
+            table.growth_left,
+            ctrl as u64,
+            |addr, len| {
+                let mut buffer = vec![0u8; len];
+                match reader.read_at(addr, &mut buffer) {
+                    Ok(()) => Some(buffer),
+                    Err(err) => {
+                        error = Some(err.kind());
+                        None
+                    }
+                }
+            },
+        );
+        match (frozen, error) {
+            (Err(ExtractError::ReadFailed { .. }), Some(kind)) => Err(ExtractError::Io(kind)),
+            (frozen, _) => frozen,
+        }
+    }
+
+    /// Assembles a frozen map from the fields of a map in another address space,
+    /// e.g. found in a coredump with the help of debug info. `ctrl_addr` is the
+    /// value of `ctrl` there. The table allocation starts `calculate_layout_for`'s
+    /// offset before it, and exactly that region is read through `read`, which is
+    /// given an address and a length. Nothing is read for the empty singleton, and
+    /// nothing larger than `MAX_EXTRACT_LEN`.
+    pub fn from_parts(
+        table_layout: TableLayout,
+        hash_builder: RandomState,
+        bucket_mask: usize,
+        items: usize,
+        growth_left: usize,
+        ctrl_addr: u64,
+        mut read: impl FnMut(u64, usize) -> Option<Vec<u8>>,
+    ) -> Result<Self, ExtractError> {
+        if ctrl_addr == 0 {
+            return Err(ExtractError::NullCtrl);
+        }
+        let table = RawTableInner {
+            bucket_mask,
+            // an address in the other process, fixed up on reconstruct
+            ctrl: NonNull::dangling(),
+            growth_left,
+            items,
+        };
+        if !table_layout.is_valid() {
+            return Err(ExtractError::InvalidTableLayout);
+        }
+        let buckets = bucket_mask
+            .checked_add(1)
+            .filter(|buckets| buckets.is_power_of_two())
+            .ok_or(ExtractError::NonPowerOfTwoBuckets)?;
+        if table.items > buckets {
+            return Err(ExtractError::ItemsExceedBuckets);
+        }
+        let memory = match table.reallocation(&table_layout) {
+            // the empty singleton points to a static, there is no allocation to read
+            None if table.is_empty_singleton() => Memory::default(),
+            None => return Err(ExtractError::InvalidTableLayout),
+            Some((offset, layout)) => {
+                let addr = ctrl_addr
+                    .checked_sub(offset as u64)
+                    .ok_or(ExtractError::AddressOverflow)?;
+                let len = layout.size();
+                if len > MAX_EXTRACT_LEN {
+                    return Err(ExtractError::TableTooLarge(len));
+                }
+                match read(addr, len) {
+                    Some(buffer) if buffer.len() == len => {
+                        Memory::Owned(AlignedBuffer::new(&buffer, table_layout.memory_align()))
+                    }
+                    _ => return Err(ExtractError::ReadFailed { addr, len }),
+                }
+            }
+        };
+
+        Ok(Self {
+            table_layout,
+            hashmap: HashMap {
+                hash_builder,
+                table: RawTable { table },
+            },
+            memory,
+            type_fingerprint: None,
+        })
+    }
+}
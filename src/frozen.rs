@@ -1,21 +1,58 @@
+use crate::{iter::is_full, AlignedBuffer, ConstructError, LoadError, Memory, TypeMismatch};
 use core::{alloc::Layout, ptr::NonNull};
-use std::fmt::Debug;
+use std::{
+    any::TypeId,
+    fmt::Debug,
+    io::{self, Write},
+    marker::PhantomData,
+    ops::Deref,
+};
 
 pub const RANDOM_STATE_TYPE_NAME: &str = "std::collections::hash::map::RandomState";
 pub const GLOBAL_ALLOC_TYPE_NAME: &str = "alloc::alloc::Global";
 
+/// Every stored blob starts with these bytes
+pub const MAGIC: [u8; 4] = *b"FHBM";
+/// Version of the stored format, bumped on every incompatible change
+pub const FORMAT_VERSION: u32 = 3;
+
+/// major.minor.patch of this crate, each saturated to a byte
+fn crate_version() -> [u8; 3] {
+    [
+        env!("CARGO_PKG_VERSION_MAJOR"),
+        env!("CARGO_PKG_VERSION_MINOR"),
+        env!("CARGO_PKG_VERSION_PATCH"),
+    ]
+    .map(|v| {
+        v.parse::<u64>()
+            .map_or(u8::MAX, |v| v.min(u8::MAX as u64) as u8)
+    })
+}
+
+/// Same as hashbrown's `Group::static_empty`: the control bytes of the empty
+/// singleton, aligned for group loads. `Group::WIDTH` is at most 16.
+#[repr(C, align(16))]
+struct EmptyGroup([u8; 16]);
+
+static EMPTY_GROUP: EmptyGroup = EmptyGroup([0xFF; 16]);
+
+/// `memory` borrows the blob it was loaded from when possible; use
+/// `load_owned` or `into_owned` to detach it from the source buffer.
 #[derive(Clone)]
-pub struct FrozenHashMap<S = RandomState> {
+pub struct FrozenHashMap<'a, S = RandomState> {
     pub table_layout: TableLayout,
     pub hashmap: HashMap<S>,
-    pub memory: Vec<u8>,
+    pub memory: Memory<'a>,
+    /// Hash of the type name of `(K, V)`, None if the types were not known
+    pub type_fingerprint: Option<u64>,
 }
 
-impl<S: Debug> Debug for FrozenHashMap<S> {
+impl<S: Debug> Debug for FrozenHashMap<'_, S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("FrozenHashMap")
             .field("table_layout", &self.table_layout)
             .field("hashmap", &self.hashmap)
+            .field("type_fingerprint", &self.type_fingerprint)
             .field(
                 "memory",
                 &format!("<binary data of size {}>", self.memory.len()),
@@ -24,13 +61,74 @@ impl<S: Debug> Debug for FrozenHashMap<S> {
     }
 }
 
+/// Metadata read from a blob by `peek_header`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialInfo {
+    pub table_layout: TableLayout,
+    pub items: usize,
+    /// `bucket_mask + 1`, or None if that overflows
+    pub buckets: Option<usize>,
+    /// Length of `memory` according to the length prefix, if present
+    pub stored_memory_len: Option<usize>,
+    /// Length of `memory` computed from `table_layout` and `buckets`
+    pub expected_memory_len: Option<usize>,
+    /// Number of bytes actually present after the header
+    pub available_memory_len: usize,
+}
+
+/// A std `HashMap` whose table lives in a borrowed slice, returned by
+/// `reconstruct_borrowed`. The map is never dropped, so the slice is never freed.
+pub struct BorrowedHashMap<'a, K, V> {
+    hashmap: HashMap<RandomState>,
+    _memory: PhantomData<&'a std::collections::HashMap<K, V>>,
+}
+
+impl<K, V> Deref for BorrowedHashMap<'_, K, V> {
+    type Target = std::collections::HashMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            &*(&self.hashmap as *const HashMap<RandomState>
+                as *const std::collections::HashMap<K, V>)
+        }
+    }
+}
+
+/// Byte identity: the header fields and the memory must be identical, except for
+/// the address of `ctrl`. Two maps with the same entries but different seeds,
+/// capacities or insertion histories are not equal; use `eq_as` for that.
+impl<S: PartialEq> PartialEq for FrozenHashMap<'_, S> {
+    fn eq(&self, other: &Self) -> bool {
+        let (a, b) = (&self.hashmap.table.table, &other.hashmap.table.table);
+        self.table_layout == other.table_layout
+            && self.hashmap.hash_builder == other.hashmap.hash_builder
+            && (a.bucket_mask, a.growth_left, a.items) == (b.bucket_mask, b.growth_left, b.items)
+            && self.type_fingerprint == other.type_fingerprint
+            && self.memory[..] == other.memory[..]
+    }
+}
+
+impl<S: Eq> Eq for FrozenHashMap<'_, S> {}
+
+/// Consistent with `PartialEq`, i.e. hashes the bytes, not the entries
+impl<S: std::hash::Hash> std::hash::Hash for FrozenHashMap<'_, S> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let table = &self.hashmap.table.table;
+        self.table_layout.hash(state);
+        self.hashmap.hash_builder.hash(state);
+        (table.bucket_mask, table.growth_left, table.items).hash(state);
+        self.type_fingerprint.hash(state);
+        self.memory[..].hash(state);
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct HashMap<S = RandomState> {
     pub hash_builder: S,
     pub table: RawTable,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RandomState {
     pub k0: u64,
     pub k1: u64,
@@ -49,7 +147,7 @@ pub struct RawTableInner {
     pub items: usize,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct TableLayout {
     pub size: usize,
     pub ctrl_align: usize,
@@ -67,6 +165,33 @@ impl TableLayout {
         }
     }
 
+    /// Length of the allocation of a table with `bucket_mask + 1` buckets, i.e. the
+    /// length of `memory`, which is 0 for the empty singleton. None if `bucket_mask`
+    /// is not one less than a power of two, `ctrl_align` is not a power of two, or
+    /// the length overflows.
+    pub fn expected_memory_len(&self, bucket_mask: usize) -> Option<usize> {
+        let buckets = bucket_mask.checked_add(1)?;
+        if !buckets.is_power_of_two() || !self.ctrl_align.is_power_of_two() {
+            return None;
+        }
+        if bucket_mask == 0 {
+            return Some(0);
+        }
+        Some(self.calculate_layout_for(buckets)?.0.size())
+    }
+
+    /// Whether `ctrl_align` is a power of two and at least `Group::WIDTH`, as
+    /// `new` makes it; std loads the control bytes a group at a time, aligned
+    pub(crate) fn is_valid(&self) -> bool {
+        self.ctrl_align.is_power_of_two() && self.ctrl_align >= crate::Group::WIDTH
+    }
+
+    /// The alignment `memory` is kept at: `ctrl_align`, but never less than
+    /// `Group::WIDTH`, whatever an untrusted blob says
+    pub(crate) fn memory_align(&self) -> usize {
+        self.ctrl_align.max(crate::Group::WIDTH)
+    }
+
     pub fn calculate_layout_for(&self, buckets: usize) -> Option<(Layout, usize)> {
         assert!(buckets.is_power_of_two());
 
@@ -102,26 +227,78 @@ impl RawTableInner {
         }
     }
 
-    fn buckets(&self) -> usize {
+    pub(crate) fn buckets(&self) -> usize {
         self.bucket_mask + 1
     }
 
-    fn is_empty_singleton(&self) -> bool {
+    /// Same as hashbrown's `bucket_mask_to_capacity`: 7/8 of the buckets, or
+    /// one less than the buckets for small tables
+    pub(crate) fn bucket_capacity(&self) -> usize {
+        if self.bucket_mask < 8 {
+            self.bucket_mask
+        } else {
+            (self.bucket_mask + 1) / 8 * 7
+        }
+    }
+
+    pub(crate) fn is_empty_singleton(&self) -> bool {
         self.bucket_mask == 0
     }
 }
 
-impl FrozenHashMap<RandomState> {
+impl<'a> FrozenHashMap<'a, RandomState> {
+    /// The hasher is fixed to `RandomState` by the signature;
+    /// use `try_construct` if the map's hasher is only known generically.
     pub fn construct<K, V>(hashmap: &std::collections::HashMap<K, V>) -> Self {
         Self::construct_with(
             unsafe {
                 core::slice::from_raw_parts(
-                    std::mem::transmute(hashmap as *const _),
+                    hashmap as *const std::collections::HashMap<K, V> as *const u8,
                     std::mem::size_of::<std::collections::HashMap<K, V>>(),
                 )
             },
             TableLayout::new(Layout::new::<(K, V)>()),
         )
+        .with_type_fingerprint::<K, V>()
+    }
+
+    /// Alias of `construct`
+    pub fn freeze<K, V>(hashmap: &std::collections::HashMap<K, V>) -> Self {
+        Self::construct(hashmap)
+    }
+
+    /// A `HashSet<T>` is a `HashMap<T, ()>` underneath, so it is frozen as one.
+    /// `(T, ())` has the size and alignment of `T`, including when `T` is zero-sized.
+    pub fn construct_set<T>(hashset: &std::collections::HashSet<T>) -> Self {
+        assert_eq!(
+            std::mem::size_of::<std::collections::HashSet<T>>(),
+            std::mem::size_of::<std::collections::HashMap<T, ()>>()
+        );
+        Self::construct(unsafe {
+            &*(hashset as *const std::collections::HashSet<T>
+                as *const std::collections::HashMap<T, ()>)
+        })
+    }
+
+    /// Like `construct`, but accepts a map with any hasher and fails with
+    /// `UnsupportedHasher` unless it is `std::collections::hash_map::RandomState`,
+    /// instead of transmuting a hasher of the wrong type.
+    pub fn try_construct<K, V, S: 'static>(
+        hashmap: &std::collections::HashMap<K, V, S>,
+    ) -> Result<Self, ConstructError> {
+        if TypeId::of::<S>() != TypeId::of::<std::collections::hash_map::RandomState>() {
+            return Err(ConstructError::UnsupportedHasher(std::any::type_name::<S>()));
+        }
+        Ok(Self::construct_with(
+            unsafe {
+                core::slice::from_raw_parts(
+                    hashmap as *const std::collections::HashMap<K, V, S> as *const u8,
+                    std::mem::size_of::<std::collections::HashMap<K, V, S>>(),
+                )
+            },
+            TableLayout::new(Layout::new::<(K, V)>()),
+        )
+        .with_type_fingerprint::<K, V>())
     }
 
     pub fn construct_with(hashmap: &[u8], table_layout: TableLayout) -> Self {
@@ -130,113 +307,1093 @@ impl FrozenHashMap<RandomState> {
             unsafe { std::ptr::read_unaligned(hashmap.as_ptr() as *const _) };
         let memory = if let Some((location, layout)) = hashmap.table.table.allocation(&table_layout)
         {
-            let location: &[u8] =
-                unsafe { core::slice::from_raw_parts(location as *const u8, layout.size()) };
-            location.to_vec()
+            let location: &[u8] = unsafe { core::slice::from_raw_parts(location, layout.size()) };
+            Memory::Owned(AlignedBuffer::new(location, table_layout.ctrl_align))
         } else {
-            vec![]
+            Memory::default()
         };
         Self {
             table_layout,
             hashmap,
             memory,
+            type_fingerprint: None,
+        }
+    }
+
+    pub(crate) fn with_type_fingerprint<K, V>(mut self) -> Self {
+        self.type_fingerprint = Some(type_fingerprint::<K, V>());
+        self
+    }
+
+    /// Like `reconstruct`, but first checks `(K, V)` against the type fingerprint
+    /// recorded by `construct`. `type_name` is not guaranteed to be stable across
+    /// compiler versions, so this is only a best-effort guard against reading the
+    /// map as a different type of the same size. It passes if no fingerprint was recorded.
+    pub fn reconstruct_checked<K, V>(
+        &mut self,
+    ) -> Result<Option<&std::collections::HashMap<K, V>>, TypeMismatch> {
+        if let Some(expected) = self.type_fingerprint {
+            let found = type_fingerprint::<K, V>();
+            if expected != found {
+                return Err(TypeMismatch::Fingerprint { expected, found });
+            }
+        }
+        Ok(self.reconstruct())
+    }
+
+    /// Checks that the map can be reconstructed as a `HashMap<K, V>`: that `(K, V)`
+    /// has the size of a bucket and fits its alignment, and that the std map has
+    /// the size of the frozen one. Unlike `reconstruct_checked`, this only looks at
+    /// sizes and alignments, so e.g. `(u32, f32)` passes for `(u32, u32)`.
+    pub fn can_reconstruct<K, V>(&self) -> Result<(), TypeMismatch> {
+        self.check_bucket_type::<K, V>()?;
+        let expected = std::mem::size_of::<HashMap<RandomState>>();
+        let found = std::mem::size_of::<std::collections::HashMap<K, V>>();
+        if expected != found {
+            return Err(TypeMismatch::MapSize { expected, found });
         }
+        Ok(())
     }
 
+    /// Alias of `reconstruct`
+    pub fn thaw<K, V>(&mut self) -> Option<&std::collections::HashMap<K, V>> {
+        self.reconstruct()
+    }
+
+    /// None if `can_reconstruct` fails, if the memory does not match the table, or
+    /// if it is not aligned to `ctrl_align`. The empty singleton, i.e. a map that
+    /// never allocated, has no memory and reconstructs to an empty map.
+    ///
+    /// `ctrl` is fixed up to point into `memory` on every call, so a frozen map
+    /// stays valid wherever its memory ends up: after a move, a clone, a `load`
+    /// into another buffer, or in the child of a `fork()`.
     pub fn reconstruct<K, V>(&mut self) -> Option<&std::collections::HashMap<K, V>> {
+        self.can_reconstruct::<K, V>().ok()?;
+        self.fix_ctrl()?;
+        unsafe {
+            // this is the crazy part
+            Some(
+                &*(&self.hashmap as *const HashMap<RandomState>
+                    as *const std::collections::HashMap<K, V>),
+            )
+        }
+    }
+
+    /// Reverse of `construct_set`. None if the memory does not match the table.
+    pub fn reconstruct_set<T>(&mut self) -> Option<&std::collections::HashSet<T>> {
+        let hashmap = self.reconstruct::<T, ()>()?;
+        unsafe {
+            Some(
+                &*(hashmap as *const std::collections::HashMap<T, ()>
+                    as *const std::collections::HashSet<T>),
+            )
+        }
+    }
+
+    /// Reconstructs a `HashMap` directly on top of `memory`, e.g. a slice of an mmaped
+    /// coredump, without copying it. The returned map borrows `memory`; the map
+    /// struct itself cannot live in the slice, hence the wrapper.
+    ///
+    /// Returns None if `table_layout` is invalid or does not fit `(K, V)`, if the
+    /// std map does not have the size of the frozen one, if `memory` does not have
+    /// the size hashbrown computes for `table_layout` and `bucket_mask`, or if the
+    /// ctrl bytes in it are not aligned to `table_layout.ctrl_align`, which
+    /// hashbrown requires for its group loads.
+    ///
+    /// # Safety
+    ///
+    /// `memory` must hold a table of `(K, V)` as laid out by hashbrown, with
+    /// `items` full buckets and control bytes consistent with `bucket_mask`, and
+    /// its keys must have been hashed with `hash_builder`. Every `K` and `V` in it
+    /// must be valid to read in this process, i.e. must not point to memory of the
+    /// process the table came from. `memory` must not be mutated while the
+    /// returned map is alive.
+    pub unsafe fn reconstruct_borrowed<'b, K, V>(
+        memory: &'b [u8],
+        table_layout: TableLayout,
+        hash_builder: RandomState,
+        bucket_mask: usize,
+        items: usize,
+        growth_left: usize,
+    ) -> Option<BorrowedHashMap<'b, K, V>> {
+        // the same checks as `can_reconstruct`, which needs a frozen map
+        if !table_layout.is_valid()
+            || std::mem::size_of::<(K, V)>() != table_layout.size
+            || std::mem::align_of::<(K, V)>() > table_layout.ctrl_align
+            || std::mem::size_of::<HashMap<RandomState>>()
+                != std::mem::size_of::<std::collections::HashMap<K, V>>()
+        {
+            return None;
+        }
+        let buckets = bucket_mask.checked_add(1)?;
+        if bucket_mask == 0 || !buckets.is_power_of_two() {
+            return None;
+        }
+        let (layout, ctrl_offset) = table_layout.calculate_layout_for(buckets)?;
+        if layout.size() != memory.len() {
+            return None;
+        }
+        let ctrl = unsafe { memory.as_ptr().add(ctrl_offset) };
+        if ctrl as usize % table_layout.ctrl_align != 0 {
+            return None;
+        }
+        Some(BorrowedHashMap {
+            hashmap: HashMap {
+                hash_builder,
+                table: RawTable {
+                    table: RawTableInner {
+                        bucket_mask,
+                        ctrl: NonNull::new(ctrl as *mut u8)?,
+                        growth_left,
+                        items,
+                    },
+                },
+            },
+            _memory: PhantomData,
+        })
+    }
+
+    /// Like `reconstruct`, but first recomputes `growth_left` from the control
+    /// bytes, for dumps where `growth_left` is corrupt but `items` and the control
+    /// bytes are intact. Same as hashbrown, deleted buckets count as used.
+    pub fn reconstruct_recompute_growth<K, V>(
+        &mut self,
+    ) -> Option<&std::collections::HashMap<K, V>> {
+        if !self.hashmap.table.table.is_empty_singleton() {
+            let deleted = self.ctrl_bytes()?.iter().filter(|c| **c == 0x80).count();
+            let table = &mut self.hashmap.table.table;
+            table.growth_left = table
+                .bucket_capacity()
+                .checked_sub(table.items)?
+                .checked_sub(deleted)?;
+        } else {
+            self.hashmap.table.table.growth_left = 0;
+        }
+        self.reconstruct()
+    }
+
+    /// Copies the table into a new allocation with the exact `Layout` hashbrown
+    /// computes for `(K, V)`, so the returned map can grow and free it like any other.
+    /// `K` and `V` must be `Copy`, because the map drops its entries and they must
+    /// not own memory of the process the map was frozen in.
+    /// None if `can_reconstruct` fails or `(K, V)` does not match the table layout.
+    pub fn into_hashmap<K: Copy, V: Copy>(&self) -> Option<std::collections::HashMap<K, V>> {
+        // the layout must be exact, as the returned map frees the table with it
+        self.can_reconstruct::<K, V>().ok()?;
+        if self.table_layout != TableLayout::new(Layout::new::<(K, V)>()) {
+            return None;
+        }
+        let table = &self.hashmap.table.table;
+        if table.is_empty_singleton() {
+            return Some(std::collections::HashMap::with_hasher(unsafe {
+                std::mem::transmute_copy(&self.hashmap.hash_builder)
+            }));
+        }
+        // the returned map trusts `growth_left` to know when to grow, so a table
+        // claiming more room than it has would be probed while full
+        let buckets = table
+            .bucket_mask
+            .checked_add(1)
+            .filter(|buckets| buckets.is_power_of_two())?;
+        if table.items > buckets
+            || table.items.checked_add(table.growth_left)? > table.bucket_capacity()
+        {
+            return None;
+        }
+        let (offset, layout) = table.reallocation(&self.table_layout)?;
+        if layout.size() != self.memory.len() {
+            return None;
+        }
+        let ptr = unsafe { std::alloc::alloc(layout) };
+        if ptr.is_null() {
+            std::alloc::handle_alloc_error(layout);
+        }
+        let mut hashmap = self.hashmap.clone();
+        unsafe {
+            core::ptr::copy_nonoverlapping(self.memory.as_ptr(), ptr, layout.size());
+            hashmap.table.table.ctrl = NonNull::new_unchecked(ptr.add(offset));
+            Some(std::mem::transmute_copy(&hashmap))
+        }
+    }
+
+    /// Like `into_hashmap`, but the returned map has room for `additional` more
+    /// entries, and `K` and `V` only need to be `Clone`. The entries are cloned and
+    /// rehashed into a new table, so unlike `reconstruct`, which aliases the frozen
+    /// table as is, the iteration order generally differs from the original map.
+    /// The seed is kept.
+    pub fn into_hashmap_with_capacity<K: std::hash::Hash + Eq + Clone, V: Clone>(
+        &self,
+        additional: usize,
+    ) -> Option<std::collections::HashMap<K, V>> {
+        // checked up front, as the empty singleton has no entries to check
+        self.check_bucket_type::<K, V>().ok()?;
+        let entries = self.typed_iter::<K, V>();
+        let mut hashmap = std::collections::HashMap::with_capacity_and_hasher(
+            self.len().checked_add(additional)?,
+            unsafe {
+                std::mem::transmute_copy::<_, std::collections::hash_map::RandomState>(
+                    &self.hashmap.hash_builder,
+                )
+            },
+        );
+        if !self.hashmap.table.table.is_empty_singleton() {
+            hashmap.extend(entries?.cloned());
+        }
+        Some(hashmap)
+    }
+
+    /// Like `into_hashmap`, but hands `memory` itself over to the returned map
+    /// instead of copying it, if it is not borrowed. This works because an owned
+    /// `memory` is allocated by the global allocator with the exact `Layout`
+    /// hashbrown computes for `(K, V)`, so the map can grow and free it like any
+    /// other. None if `(K, V)` does not match the table layout, or if the memory
+    /// does not match the table.
+    ///
+    /// # Safety
+    ///
+    /// The returned map owns and eventually drops its entries. Every `K` and `V`
+    /// in `memory` must be valid in this process, and must not be owned by anything
+    /// else: e.g. a `String` copied by `construct` from a map that is still alive,
+    /// or dropped since, would be freed twice. `Copy` types are always fine.
+    pub unsafe fn into_std_hashmap<K, V>(self) -> Option<std::collections::HashMap<K, V>> {
+        if self.table_layout != TableLayout::new(Layout::new::<(K, V)>()) {
+            return None;
+        }
         assert_eq!(
             std::mem::size_of::<HashMap<RandomState>>(),
             std::mem::size_of::<std::collections::HashMap<K, V>>()
         );
-        if self.memory.is_empty() {
+        let mut frozen = self.into_owned();
+        // the map frees the buffer with the `Layout` of the table, so it must have
+        // been allocated with exactly that; `memory` is public and may not have been
+        let align = frozen.table_layout.ctrl_align;
+        if let Memory::Owned(buffer) = &frozen.memory {
+            if buffer.align() != align && !buffer.is_empty() {
+                frozen.memory = Memory::Owned(AlignedBuffer::new(buffer, align));
+            }
+        }
+        // also checks that the length of `memory` is the size of the `Layout`
+        frozen.fix_ctrl()?;
+        let FrozenHashMap {
+            hashmap, memory, ..
+        } = frozen;
+        // from now on freed by the returned map; nothing to free for the singleton
+        std::mem::forget(memory);
+        Some(std::mem::transmute_copy(&hashmap))
+    }
+
+    /// The header describes the platform it was stored on: the endianness, the
+    /// pointer width, `Group::WIDTH` and the sizes of `TableLayout` and `HashMap`.
+    /// The format version and the memory length prefix are little-endian, the
+    /// other fields are in the byte order of the platform, which `load` swaps if
+    /// needed. `ctrl` is not stored, it is fixed up on `reconstruct`.
+    /// Note that the entries in `memory` are always in the byte order of the
+    /// platform the map was frozen on.
+    pub fn store(&self) -> Vec<u8> {
+        let hash_builder = &self.hashmap.hash_builder;
+        let mut hasher = [0u8; 16];
+        hasher[..8].copy_from_slice(&hash_builder.k0.to_ne_bytes());
+        hasher[8..].copy_from_slice(&hash_builder.k1.to_ne_bytes());
+        self.store_parts(&hasher)
+    }
+
+    /// Like `store`, but with a CRC32 of the whole blob in the header, which every
+    /// `load` verifies, failing with `ChecksumMismatch`. Catches a blob corrupted in
+    /// transit before its control bytes are trusted.
+    pub fn store_with_checksum(&self) -> Vec<u8> {
+        let mut bytes = self.store();
+        let cursor = bytes.len() - self.memory.len();
+        let checksum = CHECKSUM_PRESENT | checksum_of(&bytes, cursor) as u64;
+        bytes[cursor - 16..cursor - 8].copy_from_slice(&checksum.to_ne_bytes());
+        bytes
+    }
+
+    /// Validates the header and the table before returning: `bucket_mask + 1` must
+    /// be a power of two, `items` must not exceed the buckets, and the length of
+    /// `memory` must be the one computed by `calculate_layout_for`. The returned map
+    /// borrows `memory` from `bytes` if it is aligned to `ctrl_align`, otherwise it
+    /// is copied.
+    pub fn load(bytes: &'a [u8]) -> Result<Self, LoadError> {
+        Self::load_parts(bytes, Header::random_state)
+    }
+
+    /// Like `load`, but never copies `memory`, e.g. for a blob in an mmaped file.
+    /// Fails with `MisalignedMemory` unless `memory` starts at a multiple of
+    /// `ctrl_align`, which also aligns the ctrl bytes at the offset hashbrown
+    /// computes, instead of falling back to an aligned copy.
+    pub fn load_borrowed(bytes: &'a [u8]) -> Result<Self, LoadError> {
+        let (header, cursor) = read_header(bytes)?;
+        header.validate()?;
+        header.check_blob_len(cursor, bytes.len())?;
+        header.check_checksum(bytes, cursor)?;
+        let memory = &bytes[cursor..];
+        let align = header.table_layout.memory_align();
+        // the empty singleton has no memory to align
+        if !memory.is_empty() && memory.as_ptr() as usize % align != 0 {
+            return Err(LoadError::MisalignedMemory { align });
+        }
+        Ok(Self {
+            table_layout: header.table_layout,
+            hashmap: header.hashmap(header.random_state()?),
+            memory: Memory::Borrowed(memory),
+            type_fingerprint: header.type_fingerprint,
+        })
+    }
+
+    /// Like `load`, but also checks that the blob was stored on a platform with the
+    /// same `abi_signature` by a version of this crate not newer than this one, and
+    /// validates the table like `load_validated` does (except for scanning the
+    /// control bytes).
+    pub fn load_checked(bytes: &'a [u8]) -> Result<Self, LoadError> {
+        let (header, _) = read_header(bytes)?;
+        let expected = Self::abi_signature();
+        if header.abi_signature != expected {
+            return Err(LoadError::AbiMismatch {
+                expected,
+                found: header.abi_signature,
+            });
+        }
+        let consumer = crate_version();
+        if header.producer_version > consumer {
+            return Err(LoadError::NewerProducer {
+                producer: header.producer_version,
+                consumer,
+            });
+        }
+        let frozen = Self::load(bytes)?;
+        if !frozen.is_ctrl_mirror_consistent() {
+            return Err(LoadError::CorruptCtrlMirror);
+        }
+        Ok(frozen)
+    }
+
+    /// The version of this crate that stored the blob, as `major.minor.patch`.
+    /// Only needs the first few bytes, and works for blobs of any format version,
+    /// so it can tell which version wrote a blob `load` rejects.
+    pub fn producer_version(bytes: &[u8]) -> Option<String> {
+        if bytes.get(..MAGIC.len())? != MAGIC {
             return None;
         }
-        if let Some((offset, layout)) = self.hashmap.table.table.reallocation(&self.table_layout) {
-            assert_eq!(layout.size(), self.memory.len());
-            let address = self.memory.as_ptr() as usize + offset;
-            if address == 0 {
-                return None;
-            }
-            self.hashmap.table.table.ctrl = unsafe { NonNull::new_unchecked(address as *mut u8) };
-            unsafe {
-                // this is the crazy part
-                Some(std::mem::transmute(&self.hashmap))
+        match bytes.get(8..11)? {
+            [major, minor, patch] => Some(format!("{major}.{minor}.{patch}")),
+            _ => None,
+        }
+    }
+
+    /// A hash of everything about the platform the stored format depends on:
+    /// the size of `HashMap` and `TableLayout`, `Group::WIDTH`, the pointer
+    /// width and the endianness. Blobs are only compatible across platforms
+    /// with the same signature.
+    pub fn abi_signature() -> u64 {
+        [
+            std::mem::size_of::<HashMap<RandomState>>() as u64,
+            std::mem::size_of::<TableLayout>() as u64,
+            crate::Group::WIDTH as u64,
+            std::mem::size_of::<usize>() as u64,
+            cfg!(target_endian = "little") as u64,
+        ]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .fold(FNV_OFFSET_BASIS, fnv1a)
+    }
+
+    /// Reads whatever metadata is present at the head of a blob, which need not be
+    /// complete. None means not even `TableLayout` and `HashMap` are present.
+    pub fn peek_header(bytes: &[u8]) -> Option<PartialInfo> {
+        let (
+            Header {
+                table_layout,
+                table,
+                length,
+                ..
+            },
+            cursor,
+        ) = read_header(bytes).ok()?;
+        let expected_memory_len = table_layout.expected_memory_len(table.bucket_mask);
+        Some(PartialInfo {
+            table_layout,
+            items: table.items,
+            buckets: table.bucket_mask.checked_add(1),
+            stored_memory_len: length,
+            expected_memory_len,
+            available_memory_len: bytes.len() - cursor,
+        })
+    }
+
+    /// Checks everything that can be checked about an untrusted blob: the header,
+    /// that the table layout is consistent with the length of `memory`, and that
+    /// the control bytes agree with the number of items. `memory` is always copied
+    /// into a buffer aligned to `ctrl_align`.
+    pub fn load_validated(bytes: &[u8]) -> Result<FrozenHashMap<'static, RandomState>, LoadError> {
+        let frozen = FrozenHashMap::load(bytes)?.into_owned();
+        if let Some(ctrl) = frozen.ctrl_bytes() {
+            let full = ctrl.iter().filter(|c| is_full(**c)).count();
+            if full != frozen.hashmap.table.table.items {
+                return Err(LoadError::ItemsMismatch {
+                    items: frozen.hashmap.table.table.items,
+                    full,
+                });
             }
-        } else {
-            None
         }
+        Ok(frozen)
     }
 
-    pub fn store(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(unsafe {
-            core::slice::from_raw_parts(
-                std::mem::transmute(&self.table_layout as *const _),
-                std::mem::size_of::<TableLayout>(),
-            )
-        });
-        bytes.extend_from_slice(unsafe {
-            core::slice::from_raw_parts(
-                std::mem::transmute(&self.hashmap as *const _),
-                std::mem::size_of::<HashMap<RandomState>>(),
-            )
-        });
-        bytes.extend_from_slice(&self.memory.len().to_ne_bytes());
+    /// Same as `load`, but copies `memory` so the map does not borrow `bytes`
+    pub fn load_owned(bytes: &[u8]) -> Result<FrozenHashMap<'static, RandomState>, LoadError> {
+        FrozenHashMap::load(bytes).map(FrozenHashMap::into_owned)
+    }
+}
+
+impl<'a, S> FrozenHashMap<'a, S> {
+    /// `load` with the hasher read by `hash_builder`
+    pub(crate) fn load_parts(
+        bytes: &'a [u8],
+        hash_builder: impl FnOnce(&Header) -> Result<S, LoadError>,
+    ) -> Result<Self, LoadError> {
+        let (header, cursor) = read_header(bytes)?;
+        // also rejects data for the empty singleton, which has no allocation
+        header.validate()?;
+        header.check_blob_len(cursor, bytes.len())?;
+        header.check_checksum(bytes, cursor)?;
+        Ok(Self {
+            table_layout: header.table_layout,
+            hashmap: header.hashmap(hash_builder(&header)?),
+            memory: borrow_if_aligned(&bytes[cursor..], header.table_layout.memory_align()),
+            type_fingerprint: header.type_fingerprint,
+        })
+    }
+
+    /// `store` with the hasher already turned into bytes
+    pub(crate) fn store_parts(&self, hasher: &[u8]) -> Vec<u8> {
+        let table = &self.hashmap.table.table;
+        let header_len = HEADER_PREFIX_LEN + hasher.len() + 24;
+        let mut bytes = Vec::with_capacity(header_len + self.memory.len());
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&crate_version());
+        bytes.extend_from_slice(&Platform::native().to_bytes());
+        for field in [
+            FrozenHashMap::abi_signature(),
+            self.table_layout.size as u64,
+            self.table_layout.ctrl_align as u64,
+            table.bucket_mask as u64,
+            table.growth_left as u64,
+            table.items as u64,
+            hasher.len() as u64,
+        ] {
+            bytes.extend_from_slice(&field.to_ne_bytes());
+        }
+        bytes.extend_from_slice(hasher);
+        bytes.extend_from_slice(&self.type_fingerprint.unwrap_or(0).to_ne_bytes());
+        // no checksum, see `store_with_checksum`
+        bytes.extend_from_slice(&0u64.to_ne_bytes());
+        bytes.extend_from_slice(&(self.memory.len() as u64).to_le_bytes());
         bytes.extend_from_slice(&self.memory);
         bytes
     }
 
-    /// None means failed to load
-    pub fn load(bytes: &[u8]) -> Option<Self> {
-        let mut cursor = 0;
-        let chunk = std::mem::size_of::<TableLayout>();
-        if cursor + chunk > bytes.len() {
+    /// Points `ctrl` into `memory`, or to a static group for the empty singleton.
+    /// None if `memory` does not match the table.
+    pub(crate) fn fix_ctrl(&mut self) -> Option<()> {
+        self.hashmap.table.table.ctrl = self.ctrl_in_memory()?;
+        Some(())
+    }
+
+    /// Where `fix_ctrl` points `ctrl`
+    pub(crate) fn ctrl_in_memory(&self) -> Option<NonNull<u8>> {
+        if self.hashmap.table.table.is_empty_singleton() {
+            if !self.memory.is_empty() {
+                return None;
+            }
+            // like hashbrown, point the empty singleton to a static group of EMPTY
+            return Some(NonNull::from(&EMPTY_GROUP.0).cast());
+        }
+        if self.memory.is_empty() || !self.is_memory_aligned() {
             return None;
         }
-        let table_layout: TableLayout =
-            unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const _) };
-        cursor += chunk;
-        let chunk = std::mem::size_of::<HashMap<RandomState>>();
-        if cursor + chunk > bytes.len() {
+        let (offset, layout) = self.hashmap.table.table.reallocation(&self.table_layout)?;
+        if layout.size() != self.memory.len() {
             return None;
         }
-        let hashmap: HashMap<RandomState> =
-            unsafe { std::ptr::read_unaligned(bytes.as_ptr().add(cursor) as *const _) };
-        cursor += chunk;
-        let chunk = 8;
-        if cursor + chunk > bytes.len() {
-            return None;
-        }
-        let ll = [
-            bytes[cursor],
-            bytes[cursor + 1],
-            bytes[cursor + 2],
-            bytes[cursor + 3],
-            bytes[cursor + 4],
-            bytes[cursor + 5],
-            bytes[cursor + 6],
-            bytes[cursor + 7],
-        ];
-        cursor += chunk;
-        let length = usize::from_ne_bytes(ll);
-        if cursor + length != bytes.len() {
-            return None;
-        }
-        let memory = bytes[cursor..].to_vec();
-        Some(Self {
-            table_layout,
-            hashmap,
-            memory,
-        })
+        NonNull::new((self.memory.as_ptr() as usize + offset) as *mut u8)
+    }
+}
+
+impl<S> FrozenHashMap<'_, S> {
+    /// Whether `memory` starts at a multiple of `ctrl_align`, and of `Group::WIDTH`
+    /// even if `ctrl_align` is smaller. Every `load` and `construct` ensures it, but
+    /// `memory` is public and could be swapped for a misaligned slice, whose
+    /// buckets cannot be dereferenced.
+    pub(crate) fn is_memory_aligned(&self) -> bool {
+        self.memory.as_ptr() as usize % self.table_layout.memory_align() == 0
+    }
+
+    /// Checks that `(K, V)` has the size of a bucket, and an alignment the buckets
+    /// have, which is what reading entries in place needs
+    pub(crate) fn check_bucket_type<K, V>(&self) -> Result<(), TypeMismatch> {
+        let found = std::mem::size_of::<(K, V)>();
+        if found != self.table_layout.size {
+            return Err(TypeMismatch::Size {
+                expected: self.table_layout.size,
+                found,
+            });
+        }
+        let found = std::mem::align_of::<(K, V)>();
+        if found > self.table_layout.ctrl_align {
+            return Err(TypeMismatch::Align {
+                max: self.table_layout.ctrl_align,
+                found,
+            });
+        }
+        Ok(())
     }
 
     pub fn len(&self) -> usize {
         self.hashmap.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashmap.is_empty()
+    }
+
+    /// The control byte of each bucket, None for the empty singleton
+    /// or if `memory` does not match the table layout
+    pub(crate) fn ctrl_bytes(&self) -> Option<&[u8]> {
+        let table = &self.hashmap.table.table;
+        let (offset, layout) = table.reallocation(&self.table_layout)?;
+        if layout.size() != self.memory.len() {
+            return None;
+        }
+        Some(&self.memory[offset..offset + table.buckets()])
+    }
+
+    /// True if the trailing control bytes mirror the leading ones the way hashbrown
+    /// expects, or if there are no control bytes to check
+    pub fn is_ctrl_mirror_consistent(&self) -> bool {
+        match self.ctrl_bytes() {
+            Some(ctrl) => {
+                let tail = &self.memory[self.memory.len() - crate::Group::WIDTH..];
+                tail.iter().copied().eq(ctrl_mirror(ctrl))
+            }
+            None => true,
+        }
+    }
+
+    /// Rewrites the trailing control bytes from the leading ones, which group
+    /// loads near the end of the table rely on. Copies borrowed memory first.
+    pub fn repair_ctrl_mirror(&mut self) {
+        let Some(ctrl) = self.ctrl_bytes() else {
+            return;
+        };
+        let mirror: Vec<u8> = ctrl_mirror(ctrl).collect();
+        let memory = self.memory.to_mut(self.table_layout.memory_align());
+        let tail = memory.len() - crate::Group::WIDTH;
+        memory[tail..].copy_from_slice(&mirror);
+    }
+
+    /// Number of items the map could hold before it had to grow,
+    /// same as `std::collections::HashMap::capacity`
+    pub fn capacity(&self) -> usize {
+        self.hashmap.capacity()
+    }
+
+    /// Capacity of the table by load factor alone. This is larger than
+    /// `capacity` if the table has deleted (tombstone) buckets.
+    pub fn bucket_capacity(&self) -> usize {
+        self.hashmap.table.table.bucket_capacity()
+    }
+
+    /// Writes one `key\tvalue` line per entry, both in `Debug` format
+    pub fn dump_text<K: Debug, V: Debug>(&self, w: &mut impl Write) -> io::Result<()> {
+        if self.hashmap.is_empty() {
+            return Ok(());
+        }
+        let iter = self.iter::<K, V>().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "(K, V) does not match the table layout",
+            )
+        })?;
+        for (key, value) in iter {
+            writeln!(w, "{key:?}\t{value:?}")?;
+        }
+        Ok(())
+    }
+
+    /// Everything needed to tell why a map fails to load or reconstruct, one field
+    /// per line, for pasting into bug reports
+    pub fn diagnostic(&self) -> String {
+        use std::fmt::Write;
+
+        const CTRL_PREVIEW: usize = 16;
+        let table = &self.hashmap.table.table;
+        let mut out = String::new();
+        let _ = writeln!(out, "table_layout: {:?}", self.table_layout);
+        let _ = writeln!(out, "bucket_mask: {}", table.bucket_mask);
+        let _ = writeln!(out, "buckets: {:?}", table.bucket_mask.checked_add(1));
+        let _ = writeln!(out, "items: {}", table.items);
+        let _ = writeln!(out, "growth_left: {}", table.growth_left);
+        let _ = writeln!(out, "ctrl: {:p}", table.ctrl);
+        let _ = writeln!(out, "empty_singleton: {}", table.is_empty_singleton());
+        let _ = writeln!(out, "type_fingerprint: {:x?}", self.type_fingerprint);
+        let _ = writeln!(out, "memory.len(): {}", self.memory.len());
+        let reallocation = table
+            .bucket_mask
+            .checked_add(1)
+            .filter(|buckets| buckets.is_power_of_two() && !table.is_empty_singleton())
+            .and_then(|buckets| {
+                let (layout, offset) = self.table_layout.calculate_layout_for(buckets)?;
+                Some((layout, offset, buckets))
+            });
+        match reallocation {
+            Some((layout, offset, buckets)) => {
+                let _ = writeln!(out, "(layout, ctrl_offset): ({layout:?}, {offset})");
+                let ctrl = self.memory.get(offset..).unwrap_or_default();
+                let ctrl = &ctrl[..ctrl.len().min(buckets)];
+                let _ = writeln!(out, "ctrl bytes available: {}", ctrl.len());
+                let head = &ctrl[..ctrl.len().min(CTRL_PREVIEW)];
+                let tail = &ctrl[ctrl.len().saturating_sub(CTRL_PREVIEW)..];
+                let _ = writeln!(out, "ctrl head: {}", hex(head));
+                let _ = writeln!(out, "ctrl tail: {}", hex(tail));
+            }
+            None => {
+                let _ = writeln!(out, "(layout, ctrl_offset): None");
+            }
+        }
+        out
+    }
+
+    /// Returns all buckets as one slice if every bucket is full, in reverse bucket
+    /// order. Note that hashbrown's load factor never lets a live table fill every
+    /// bucket, so this only succeeds for tables assembled or patched by hand.
+    pub fn as_contiguous_pairs<K, V>(&self) -> Option<&[(K, V)]> {
+        self.check_bucket_type::<K, V>().ok()?;
+        let table = &self.hashmap.table.table;
+        let (offset, layout) = table.reallocation(&self.table_layout)?;
+        if layout.size() != self.memory.len() || table.items != table.buckets() {
+            return None;
+        }
+        let ctrl = &self.memory[offset..offset + table.buckets()];
+        if !ctrl.iter().all(|c| is_full(*c)) {
+            return None;
+        }
+        let data = &self.memory[offset - table.buckets() * self.table_layout.size..offset];
+        if data.as_ptr() as usize % std::mem::align_of::<(K, V)>() != 0 {
+            return None;
+        }
+        Some(unsafe {
+            core::slice::from_raw_parts(data.as_ptr() as *const (K, V), table.buckets())
+        })
+    }
+
+    /// Logical equality: true if both maps hold the same entries, regardless of
+    /// seed, capacity or bucket order. None if `(K, V)` does not have the size of
+    /// a bucket of either map.
+    pub fn eq_as<K: std::hash::Hash + Eq, V: PartialEq, T>(
+        &self,
+        other: &FrozenHashMap<'_, T>,
+    ) -> Option<bool> {
+        let entries: std::collections::HashMap<&K, &V> =
+            other.typed_iter::<K, V>()?.map(|(k, v)| (k, v)).collect();
+        let mut iter = self.typed_iter::<K, V>()?;
+        Some(self.hashmap.len() == entries.len() && iter.all(|(k, v)| entries.get(k) == Some(&v)))
+    }
+
+    pub fn into_owned(self) -> FrozenHashMap<'static, S> {
+        FrozenHashMap {
+            table_layout: self.table_layout,
+            hashmap: self.hashmap,
+            memory: self.memory.into_owned(self.table_layout.memory_align()),
+            type_fingerprint: self.type_fingerprint,
+        }
+    }
+}
+
+/// The `Group::WIDTH` control bytes hashbrown keeps after the `ctrl` of every
+/// bucket: a copy of the leading ones, preceded by EMPTY if there are fewer
+/// buckets than `Group::WIDTH`
+fn ctrl_mirror(ctrl: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    let empty = crate::Group::WIDTH.saturating_sub(ctrl.len());
+    std::iter::repeat(0xFF)
+        .take(empty)
+        .chain(ctrl.iter().copied().take(crate::Group::WIDTH - empty))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+
+fn fnv1a(hash: u64, byte: u8) -> u64 {
+    (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+}
+
+/// FNV-1a of the type name of `(K, V)`
+pub(crate) fn type_fingerprint<K, V>() -> u64 {
+    std::any::type_name::<(K, V)>()
+        .bytes()
+        .fold(FNV_OFFSET_BASIS, fnv1a)
+}
+
+/// Set in the checksum field when there is a checksum, whose CRC32 can be 0
+const CHECKSUM_PRESENT: u64 = 1 << 32;
+
+/// CRC-32 (IEEE) lookup table, same as zlib's
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB88320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC32 of a blob whose header ends at `cursor`, over everything except the
+/// checksum field itself, which is right before the memory length prefix
+fn checksum_of(bytes: &[u8], cursor: usize) -> u32 {
+    let crc = [&bytes[..cursor - 16], &bytes[cursor - 8..]]
+        .into_iter()
+        .flatten()
+        .fold(!0u32, |crc, byte| {
+            CRC32_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+        });
+    !crc
+}
+
+/// hashbrown does aligned group loads on ctrl, so `memory` can only be
+/// borrowed if it is aligned
+pub(crate) fn borrow_if_aligned(memory: &[u8], align: usize) -> Memory<'_> {
+    if memory.as_ptr() as usize % align == 0 {
+        Memory::Borrowed(memory)
+    } else {
+        Memory::Owned(AlignedBuffer::new(memory, align))
+    }
+}
+
+pub(crate) struct Header {
+    pub(crate) producer_version: [u8; 3],
+    pub(crate) big_endian: bool,
+    pub(crate) abi_signature: u64,
+    pub(crate) table_layout: TableLayout,
+    /// `ctrl` is dangling
+    pub(crate) table: RawTableInner,
+    /// The bytes of the hash_builder, in the byte order of the producer
+    pub(crate) hasher: Vec<u8>,
+    pub(crate) type_fingerprint: Option<u64>,
+    /// CRC32 of the blob, if stored with `store_with_checksum`
+    pub(crate) checksum: Option<u32>,
+    /// The memory length prefix, stored as a little-endian `u64`, if present
+    pub(crate) length: Option<usize>,
+}
+
+/// Size of everything before the hash_builder: the magic, the format version, the
+/// producer version, the platform and 7 scalar fields, the last one being the
+/// length of the hash_builder
+pub(crate) const HEADER_PREFIX_LEN: usize = MAGIC.len() + 4 + 3 + Platform::LEN + 7 * 8;
+
+/// Size of everything before `memory`, given the first `HEADER_PREFIX_LEN` bytes:
+/// the prefix, the hash_builder, the type fingerprint, the checksum and the memory
+/// length prefix
+pub(crate) fn header_len(prefix: &[u8]) -> Result<usize, LoadError> {
+    if prefix.get(..MAGIC.len()) != Some(&MAGIC[..]) {
+        return Err(LoadError::BadMagic);
+    }
+    let hasher_len = prefix
+        .get(HEADER_PREFIX_LEN - 8..HEADER_PREFIX_LEN)
+        .ok_or(LoadError::TruncatedBlob)?
+        .try_into()
+        .unwrap();
+    let hasher_len = match prefix[MAGIC.len() + 4 + 3] {
+        1 => u64::from_be_bytes(hasher_len),
+        _ => u64::from_le_bytes(hasher_len),
+    };
+    Ok(HEADER_PREFIX_LEN + 24 + check_hasher_len(hasher_len)?)
+}
+
+/// Upper bound on the size of a stored hash_builder, so a corrupt length is
+/// rejected before anything is allocated for it
+pub const MAX_HASHER_LEN: usize = 1024;
+
+fn check_hasher_len(hasher_len: u64) -> Result<usize, LoadError> {
+    usize::try_from(hasher_len)
+        .ok()
+        .filter(|&len| len <= MAX_HASHER_LEN)
+        .ok_or(LoadError::HasherTooLarge(hasher_len))
+}
+
+/// What the stored format depends on about the platform that stored it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Platform {
+    big_endian: bool,
+    pointer_width: u8,
+    group_width: u8,
+    table_layout_size: u8,
+    hashmap_size: u8,
+}
+
+impl Platform {
+    const LEN: usize = 5;
+
+    fn native() -> Self {
+        Self {
+            big_endian: cfg!(target_endian = "big"),
+            pointer_width: std::mem::size_of::<usize>() as u8,
+            group_width: crate::Group::WIDTH as u8,
+            table_layout_size: std::mem::size_of::<TableLayout>() as u8,
+            hashmap_size: std::mem::size_of::<HashMap<RandomState>>() as u8,
+        }
+    }
+
+    fn to_bytes(self) -> [u8; Self::LEN] {
+        [
+            self.big_endian as u8,
+            self.pointer_width,
+            self.group_width,
+            self.table_layout_size,
+            self.hashmap_size,
+        ]
+    }
+
+    /// Fails on anything but the endianness differing from this platform
+    fn check(bytes: &[u8]) -> Result<Self, LoadError> {
+        let native = Self::native();
+        let big_endian = match bytes[0] {
+            0 => false,
+            1 => true,
+            other => return Err(LoadError::InvalidEndianness(other)),
+        };
+        for (what, expected, found) in [
+            ("pointer width", native.pointer_width, bytes[1]),
+            ("Group::WIDTH", native.group_width, bytes[2]),
+            ("size of TableLayout", native.table_layout_size, bytes[3]),
+            ("size of HashMap", native.hashmap_size, bytes[4]),
+        ] {
+            if expected != found {
+                return Err(LoadError::PlatformMismatch {
+                    what,
+                    expected,
+                    found,
+                });
+            }
+        }
+        Ok(Self {
+            big_endian,
+            ..native
+        })
+    }
+}
+
+impl Header {
+    pub(crate) fn hashmap<S>(&self, hash_builder: S) -> HashMap<S> {
+        HashMap {
+            hash_builder,
+            table: RawTable {
+                table: self.table.clone(),
+            },
+        }
+    }
+
+    /// The hash_builder, which must be a `RandomState`
+    pub(crate) fn random_state(&self) -> Result<RandomState, LoadError> {
+        let [k0, k1] = match self.hasher.len() {
+            16 => [&self.hasher[..8], &self.hasher[8..]].map(|k| {
+                let k = k.try_into().unwrap();
+                if self.big_endian {
+                    u64::from_be_bytes(k)
+                } else {
+                    u64::from_le_bytes(k)
+                }
+            }),
+            found => {
+                return Err(LoadError::HasherSizeMismatch {
+                    expected: 16,
+                    found,
+                })
+            }
+        };
+        Ok(RandomState { k0, k1 })
+    }
+
+    /// The hash_builder, copied byte by byte into an `S`. Its bytes are opaque, so
+    /// they cannot be swapped for a platform of the other endianness.
+    ///
+    /// # Safety
+    ///
+    /// Every bit pattern of the size of `S` must be a valid `S`.
+    pub(crate) unsafe fn hasher<S>(&self) -> Result<S, LoadError> {
+        let expected = std::mem::size_of::<S>();
+        if self.hasher.len() != expected {
+            return Err(LoadError::HasherSizeMismatch {
+                expected,
+                found: self.hasher.len(),
+            });
+        }
+        if expected != 0 && self.big_endian != cfg!(target_endian = "big") {
+            return Err(LoadError::PlatformMismatch {
+                what: "endianness of hasher",
+                expected: cfg!(target_endian = "big") as u8,
+                found: self.big_endian as u8,
+            });
+        }
+        Ok(unsafe { std::ptr::read_unaligned(self.hasher.as_ptr() as *const S) })
+    }
+
+    /// Checks the table invariants, and the length prefix against the table layout
+    pub(crate) fn validate(&self) -> Result<(), LoadError> {
+        let length = self.length.ok_or(LoadError::TruncatedBlob)?;
+        validate_table(&self.table_layout, &self.table, length)
+    }
+
+    /// Checks that a blob of `total` bytes ends right after `memory`,
+    /// given that the header ends at `cursor`
+    pub(crate) fn check_blob_len(&self, cursor: usize, total: usize) -> Result<(), LoadError> {
+        let length = self.length.ok_or(LoadError::TruncatedBlob)?;
+        match cursor.checked_add(length) {
+            Some(end) if end > total => Err(LoadError::TruncatedBlob),
+            Some(end) if end < total => Err(LoadError::TrailingBytes),
+            Some(_) => Ok(()),
+            None => Err(LoadError::TruncatedBlob),
+        }
+    }
+
+    /// Verifies the checksum, if any, of a blob whose header ends at `cursor`
+    pub(crate) fn check_checksum(&self, bytes: &[u8], cursor: usize) -> Result<(), LoadError> {
+        match self.checksum {
+            Some(expected) => {
+                let found = checksum_of(bytes, cursor);
+                if found != expected {
+                    return Err(LoadError::ChecksumMismatch { expected, found });
+                }
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks `table` against `table_layout` and the length of `memory`: the buckets
+/// are a power of two, `items` fit, and `memory` is the allocation computed by
+/// `calculate_layout_for`, or empty for the empty singleton
+pub(crate) fn validate_table(
+    table_layout: &TableLayout,
+    table: &RawTableInner,
+    length: usize,
+) -> Result<(), LoadError> {
+    if !table_layout.is_valid() {
+        return Err(LoadError::InvalidTableLayout);
+    }
+    // an untrusted `bucket_mask` of `usize::MAX` would overflow `buckets()`
+    let buckets = table
+        .bucket_mask
+        .checked_add(1)
+        .filter(|buckets| buckets.is_power_of_two())
+        .ok_or(LoadError::NonPowerOfTwoBuckets)?;
+    if table.items > buckets {
+        return Err(LoadError::ItemsExceedBuckets);
+    }
+    let expected = table_layout
+        .expected_memory_len(table.bucket_mask)
+        .ok_or(LoadError::InvalidTableLayout)?;
+    if expected != length {
+        return Err(LoadError::MemorySizeMismatch {
+            expected,
+            found: length,
+        });
+    }
+    Ok(())
+}
+
+/// Reads the magic and version, the producer version, the platform, the scalar
+/// fields and the memory length prefix if present. Also returns the position
+/// right after what was read.
+pub(crate) fn read_header(bytes: &[u8]) -> Result<(Header, usize), LoadError> {
+    let mut cursor: usize = 0;
+    let mut take = |len: usize| -> Result<&[u8], LoadError> {
+        let end = cursor.checked_add(len).ok_or(LoadError::TruncatedBlob)?;
+        let chunk = bytes.get(cursor..end).ok_or(LoadError::TruncatedBlob)?;
+        cursor += len;
+        Ok(chunk)
+    };
+    if take(MAGIC.len())? != MAGIC {
+        return Err(LoadError::BadMagic);
+    }
+    let version = u32::from_le_bytes(take(4)?.try_into().unwrap());
+    if version != FORMAT_VERSION {
+        return Err(LoadError::UnsupportedVersion(version));
+    }
+    let producer_version = take(3)?.try_into().unwrap();
+    let platform = Platform::check(take(Platform::LEN)?)?;
+    let field = |chunk: &[u8]| {
+        let chunk = chunk.try_into().unwrap();
+        if platform.big_endian {
+            u64::from_be_bytes(chunk)
+        } else {
+            u64::from_le_bytes(chunk)
+        }
+    };
+    // the pointer width is the same, so every field fits in a usize
+    let abi_signature = field(take(8)?);
+    let table_layout = TableLayout {
+        size: field(take(8)?) as usize,
+        ctrl_align: field(take(8)?) as usize,
+    };
+    let table = RawTableInner {
+        bucket_mask: field(take(8)?) as usize,
+        ctrl: NonNull::dangling(),
+        growth_left: field(take(8)?) as usize,
+        items: field(take(8)?) as usize,
+    };
+    let hasher_len = check_hasher_len(field(take(8)?))?;
+    let hasher = take(hasher_len)?.to_vec();
+    let type_fingerprint = field(take(8)?);
+    let checksum = field(take(8)?);
+    let length = match take(8) {
+        Ok(chunk) => {
+            let length = u64::from_le_bytes(chunk.try_into().unwrap());
+            Some(usize::try_from(length).map_err(|_| LoadError::MemoryTooLarge(length))?)
+        }
+        Err(_) => None,
+    };
+    let header = Header {
+        producer_version,
+        big_endian: platform.big_endian,
+        abi_signature,
+        table_layout,
+        table,
+        hasher,
+        type_fingerprint: (type_fingerprint != 0).then_some(type_fingerprint),
+        checksum: (checksum & CHECKSUM_PRESENT != 0).then_some(checksum as u32),
+        length,
+    };
+    Ok((header, cursor))
 }
 
 impl<S> HashMap<S> {
     pub fn len(&self) -> usize {
         self.table.table.items
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.table.table.items + self.table.table.growth_left
+    }
 }
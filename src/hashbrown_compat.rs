@@ -0,0 +1,66 @@
+use crate::{FrozenHashMap, RandomState, TableLayout};
+use core::alloc::Layout;
+
+type StdRandomState = std::collections::hash_map::RandomState;
+
+/// `hashbrown::HashMap` and `HashSet` have the same layout as the std ones,
+/// as long as they use std's `RandomState` as the hasher.
+impl FrozenHashMap<'_, RandomState> {
+    pub fn construct_from_hashbrown<K, V>(
+        hashmap: &hashbrown::HashMap<K, V, StdRandomState>,
+    ) -> Self {
+        Self::construct_with(
+            unsafe {
+                core::slice::from_raw_parts(
+                    hashmap as *const hashbrown::HashMap<K, V, StdRandomState> as *const u8,
+                    std::mem::size_of::<hashbrown::HashMap<K, V, StdRandomState>>(),
+                )
+            },
+            TableLayout::new(Layout::new::<(K, V)>()),
+        )
+        .with_type_fingerprint::<K, V>()
+    }
+
+    pub fn reconstruct_hashbrown<K, V>(
+        &mut self,
+    ) -> Option<&hashbrown::HashMap<K, V, StdRandomState>> {
+        assert_eq!(
+            std::mem::size_of::<std::collections::HashMap<K, V>>(),
+            std::mem::size_of::<hashbrown::HashMap<K, V, StdRandomState>>()
+        );
+        let hashmap = self.reconstruct::<K, V>()?;
+        Some(unsafe {
+            &*(hashmap as *const std::collections::HashMap<K, V>
+                as *const hashbrown::HashMap<K, V, StdRandomState>)
+        })
+    }
+
+    pub fn construct_from_hashbrown_set<T>(
+        hashset: &hashbrown::HashSet<T, StdRandomState>,
+    ) -> Self {
+        Self::construct_with(
+            unsafe {
+                core::slice::from_raw_parts(
+                    hashset as *const hashbrown::HashSet<T, StdRandomState> as *const u8,
+                    std::mem::size_of::<hashbrown::HashSet<T, StdRandomState>>(),
+                )
+            },
+            TableLayout::new(Layout::new::<(T, ())>()),
+        )
+        .with_type_fingerprint::<T, ()>()
+    }
+
+    pub fn reconstruct_hashbrown_set<T>(
+        &mut self,
+    ) -> Option<&hashbrown::HashSet<T, StdRandomState>> {
+        assert_eq!(
+            std::mem::size_of::<std::collections::HashMap<T, ()>>(),
+            std::mem::size_of::<hashbrown::HashSet<T, StdRandomState>>()
+        );
+        let hashmap = self.reconstruct::<T, ()>()?;
+        Some(unsafe {
+            &*(hashmap as *const std::collections::HashMap<T, ()>
+                as *const hashbrown::HashSet<T, StdRandomState>)
+        })
+    }
+}
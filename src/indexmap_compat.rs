@@ -0,0 +1,336 @@
+use crate::{AlignedBuffer, FrozenHashMap, HashMap, Memory, RandomState, RawTable, TableLayout};
+use core::{
+    alloc::Layout,
+    marker::PhantomData,
+    mem::{ManuallyDrop, MaybeUninit},
+};
+use indexmap::IndexMap;
+use std::{hash::Hash, ops::Deref};
+
+/// Every blob stored by `FrozenIndexMap::store` starts with these bytes
+pub const INDEXMAP_MAGIC: [u8; 4] = *b"FHBI";
+
+/// A frozen `indexmap::IndexMap`: the backing `Vec` of entries, and the hashbrown
+/// table of indices into it, which is frozen as a map of `usize` to `()`.
+#[derive(Clone)]
+pub struct FrozenIndexMap<'a> {
+    pub hash_builder: RandomState,
+    pub entry_layout: EntryLayout,
+    pub len: usize,
+    /// The entries in insertion order
+    pub memory: Memory<'a>,
+    /// The index table; its `hash_builder` is a copy of the one above
+    pub indices: FrozenHashMap<'a>,
+}
+
+/// Mirror of `indexmap::IndexMap<K, V>`, in the same way `HashMap` mirrors the
+/// std one: the index table is a `hashbrown::HashTable<usize>`, i.e. a `RawTable`,
+/// and a `Vec<T>` has the same layout for every sized `T`
+struct IndexMapMirror {
+    core: Core,
+    hash_builder: RandomState,
+}
+
+struct Core {
+    indices: RawTable,
+    entries: Vec<u8>,
+}
+
+/// Mirror of the private entry type of `indexmap`
+struct Bucket<K, V> {
+    _hash: usize,
+    key: K,
+    value: V,
+}
+
+/// An `IndexMap` whose entries and index table live in a `FrozenIndexMap`,
+/// returned by `reconstruct`. The map is never dropped, so nothing is freed.
+pub struct BorrowedIndexMap<'a, K, V> {
+    indexmap: ManuallyDrop<IndexMapMirror>,
+    _memory: PhantomData<&'a IndexMap<K, V>>,
+}
+
+impl<K, V> Deref for BorrowedIndexMap<'_, K, V> {
+    type Target = IndexMap<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*(&*self.indexmap as *const IndexMapMirror as *const IndexMap<K, V>) }
+    }
+}
+
+/// Where the key and value are in an entry of the backing `Vec`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct EntryLayout {
+    pub size: usize,
+    pub align: usize,
+    pub key_offset: usize,
+    pub key_size: usize,
+    pub value_offset: usize,
+    pub value_size: usize,
+}
+
+impl std::fmt::Debug for FrozenIndexMap<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FrozenIndexMap")
+            .field("hash_builder", &self.hash_builder)
+            .field("entry_layout", &self.entry_layout)
+            .field("len", &self.len)
+            .field(
+                "memory",
+                &format!("<binary data of size {}>", self.memory.len()),
+            )
+            .field("indices", &self.indices)
+            .finish()
+    }
+}
+
+impl EntryLayout {
+    /// The layout of an entry of `IndexMap<K, V>` in this process
+    pub fn of<K, V>() -> Self {
+        let entry = MaybeUninit::<Bucket<K, V>>::uninit();
+        let base = entry.as_ptr();
+        let (key, value) = unsafe {
+            (
+                core::ptr::addr_of!((*base).key) as usize,
+                core::ptr::addr_of!((*base).value) as usize,
+            )
+        };
+        Self {
+            size: std::mem::size_of::<Bucket<K, V>>(),
+            align: std::mem::align_of::<Bucket<K, V>>(),
+            key_offset: key - base as usize,
+            key_size: std::mem::size_of::<K>(),
+            value_offset: value - base as usize,
+            value_size: std::mem::size_of::<V>(),
+        }
+    }
+}
+
+impl FrozenIndexMap<'_> {
+    /// Panics if `IndexMap` is not laid out the way `IndexMapMirror` assumes
+    pub fn construct_indexmap<K, V>(indexmap: &IndexMap<K, V>) -> FrozenIndexMap<'static> {
+        assert_eq!(
+            std::mem::size_of::<IndexMapMirror>(),
+            std::mem::size_of::<IndexMap<K, V>>()
+        );
+        let mirror = ManuallyDrop::new(unsafe {
+            std::ptr::read(indexmap as *const IndexMap<K, V> as *const IndexMapMirror)
+        });
+        let entries = mirror.core.entries.as_ptr() as usize;
+        assert!(
+            entries == indexmap.as_slice() as *const _ as *const u8 as usize
+                && mirror.core.entries.len() == indexmap.len()
+                && mirror.core.indices.table.items == indexmap.len()
+                && indexmap.get_index(0).map_or(true, |(key, _)| {
+                    key as *const K as usize - entries == EntryLayout::of::<K, V>().key_offset
+                }),
+            "IndexMap is not laid out as expected"
+        );
+        let indices = HashMap {
+            hash_builder: mirror.hash_builder.clone(),
+            table: mirror.core.indices.clone(),
+        };
+        let indices = FrozenHashMap::construct_with(
+            unsafe {
+                core::slice::from_raw_parts(
+                    &indices as *const HashMap<RandomState> as *const u8,
+                    std::mem::size_of::<HashMap<RandomState>>(),
+                )
+            },
+            TableLayout::new(Layout::new::<usize>()),
+        );
+        let entry_layout = EntryLayout::of::<K, V>();
+        let memory = unsafe {
+            core::slice::from_raw_parts(
+                mirror.core.entries.as_ptr(),
+                entry_layout.size * indexmap.len(),
+            )
+        };
+        FrozenIndexMap {
+            hash_builder: mirror.hash_builder.clone(),
+            entry_layout,
+            len: indexmap.len(),
+            memory: Memory::Owned(AlignedBuffer::new(memory, entry_layout.align)),
+            indices,
+        }
+    }
+
+    /// Iterates the entries in insertion order. None if `K` and `V` do not fit
+    /// the entry layout.
+    pub fn iter<'b, K: 'b, V: 'b>(&'b self) -> Option<impl Iterator<Item = (&'b K, &'b V)>> {
+        let EntryLayout {
+            size,
+            align,
+            key_offset,
+            key_size,
+            value_offset,
+            value_size,
+        } = self.entry_layout;
+        // the layout comes from the blob, so it must keep every `K` and `V` in
+        // bounds and aligned by itself
+        if key_size != std::mem::size_of::<K>()
+            || value_size != std::mem::size_of::<V>()
+            || align < std::mem::align_of::<K>().max(std::mem::align_of::<V>())
+            || !align.is_power_of_two()
+            || size % align != 0
+            || key_offset % std::mem::align_of::<K>() != 0
+            || value_offset % std::mem::align_of::<V>() != 0
+            || key_offset
+                .checked_add(key_size)
+                .map_or(true, |end| end > size)
+            || value_offset
+                .checked_add(value_size)
+                .map_or(true, |end| end > size)
+            || self.memory.as_ptr() as usize % align != 0
+            || size.checked_mul(self.len) != Some(self.memory.len())
+        {
+            return None;
+        }
+        let base = self.memory.as_ptr();
+        Some((0..self.len).map(move |i| unsafe {
+            let entry = base.add(i * size);
+            (
+                &*(entry.add(key_offset) as *const K),
+                &*(entry.add(value_offset) as *const V),
+            )
+        }))
+    }
+
+    /// Aliases the entries and the index table as an `IndexMap<K, V>`, the way
+    /// `FrozenHashMap::reconstruct` does for a `HashMap`. None if `(K, V)` does not
+    /// have the entry layout, or if the index table does not match the entries.
+    pub fn reconstruct<K, V>(&self) -> Option<BorrowedIndexMap<'_, K, V>> {
+        if self.entry_layout != EntryLayout::of::<K, V>() {
+            return None;
+        }
+        // checks the alignment and length of `memory`
+        let _ = self.iter::<K, V>()?;
+        if self.indices.table_layout != TableLayout::new(Layout::new::<usize>())
+            || self.indices.len() != self.len
+            // the empty singleton has no buckets to iterate
+            || !self.indices.hashmap.table.table.is_empty_singleton()
+                && self
+                    .indices
+                    .iter::<usize, ()>()?
+                    .any(|(index, _)| *index >= self.len)
+        {
+            return None;
+        }
+        let mut table = self.indices.hashmap.table.clone();
+        table.table.ctrl = self.indices.ctrl_in_memory()?;
+        let entries = match self.len {
+            // a `Vec` that never allocated is dangling, but aligned
+            0 => self.entry_layout.align as *mut u8,
+            _ => self.memory.as_ptr() as *mut u8,
+        };
+        Some(BorrowedIndexMap {
+            indexmap: ManuallyDrop::new(IndexMapMirror {
+                core: Core {
+                    indices: table,
+                    // the length and capacity are in entries, not bytes
+                    entries: unsafe { Vec::from_raw_parts(entries, self.len, self.len) },
+                },
+                hash_builder: self.hash_builder.clone(),
+            }),
+            _memory: PhantomData,
+        })
+    }
+
+    /// Clones the map returned by `reconstruct`, so the index table is copied
+    /// as is instead of rehashing every key
+    pub fn reconstruct_indexmap<K: Hash + Eq + Clone, V: Clone>(&self) -> Option<IndexMap<K, V>> {
+        Some(self.reconstruct::<K, V>()?.clone())
+    }
+
+    /// All integers are stored as little-endian `u64`. The index table follows
+    /// the entries, as stored by `FrozenHashMap::store`.
+    pub fn store(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&INDEXMAP_MAGIC);
+        let EntryLayout {
+            size,
+            align,
+            key_offset,
+            key_size,
+            value_offset,
+            value_size,
+        } = self.entry_layout;
+        for v in [self.hash_builder.k0, self.hash_builder.k1] {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        for v in [
+            size,
+            align,
+            key_offset,
+            key_size,
+            value_offset,
+            value_size,
+            self.len,
+            self.memory.len(),
+        ] {
+            bytes.extend_from_slice(&(v as u64).to_le_bytes());
+        }
+        bytes.extend_from_slice(&self.memory);
+        bytes.extend(self.indices.store());
+        bytes
+    }
+
+    /// None means failed to load. The returned map borrows `memory` from `bytes`
+    /// if it is suitably aligned, otherwise it is copied. The index table is
+    /// loaded with `FrozenHashMap::load_validated`, so it is always copied.
+    pub fn load(bytes: &[u8]) -> Option<FrozenIndexMap<'_>> {
+        let mut cursor = 0;
+        if bytes.get(..INDEXMAP_MAGIC.len())? != INDEXMAP_MAGIC {
+            return None;
+        }
+        cursor += INDEXMAP_MAGIC.len();
+        let mut read = || -> Option<u64> {
+            let chunk = bytes.get(cursor..cursor + 8)?;
+            cursor += 8;
+            Some(u64::from_le_bytes(chunk.try_into().unwrap()))
+        };
+        let hash_builder = RandomState {
+            k0: read()?,
+            k1: read()?,
+        };
+        let mut fields = [0usize; 8];
+        for field in fields.iter_mut() {
+            *field = usize::try_from(read()?).ok()?;
+        }
+        let [size, align, key_offset, key_size, value_offset, value_size, len, memory_len] = fields;
+        if !align.is_power_of_two() || size.checked_mul(len) != Some(memory_len) {
+            return None;
+        }
+        if len > 0
+            && (key_offset.checked_add(key_size)? > size
+                || value_offset.checked_add(value_size)? > size)
+        {
+            return None;
+        }
+        let end = cursor.checked_add(memory_len)?;
+        let indices = FrozenHashMap::load_validated(bytes.get(end..)?).ok()?;
+        if indices.len() != len {
+            return None;
+        }
+        let memory = &bytes[cursor..end];
+        let memory = if memory.as_ptr() as usize % align == 0 {
+            Memory::Borrowed(memory)
+        } else {
+            Memory::Owned(AlignedBuffer::new(memory, align))
+        };
+        Some(FrozenIndexMap {
+            hash_builder,
+            entry_layout: EntryLayout {
+                size,
+                align,
+                key_offset,
+                key_size,
+                value_offset,
+                value_size,
+            },
+            len,
+            memory,
+            indices,
+        })
+    }
+}
@@ -1,6 +1,14 @@
-use crate::FrozenHashMap;
+use crate::{FrozenHashMap, Memory};
+use std::marker::PhantomData;
+
+/// most significant bit = 0 means bucket is full
+#[inline]
+pub(crate) fn is_full(ctrl: u8) -> bool {
+    ctrl & 0x80 == 0
+}
 
 /// An iterator that yields raw pointers to buckets
+#[derive(Clone)]
 pub struct RawBucketIter<'a> {
     base: *const u8,
     cur: *const u8,
@@ -10,10 +18,29 @@ pub struct RawBucketIter<'a> {
     _memory: &'a [u8],
 }
 
-impl<S> FrozenHashMap<S> {
+/// Like `RawBucketIter`, but owns the memory it points into, so it can be
+/// returned by value or sent to another thread. The yielded pointers are valid
+/// as long as the iterator is alive.
+pub struct OwnedRawBucketIter<'a> {
+    raw: RawBucketIter<'a>,
+    _memory: Memory<'a>,
+}
+
+// the pointers only ever point into `_memory`, which is `Send` and `Sync`
+unsafe impl Send for OwnedRawBucketIter<'_> {}
+unsafe impl Sync for OwnedRawBucketIter<'_> {}
+
+/// An iterator that yields references to the key and value of each full bucket
+pub struct TypedBucketIter<'a, K, V> {
+    raw: RawBucketIter<'a>,
+    _entry: PhantomData<&'a (K, V)>,
+}
+
+impl<S> FrozenHashMap<'_, S> {
+    /// None if the memory does not match the table, or is not aligned to `ctrl_align`
     pub fn raw_iter(&self) -> Option<RawBucketIter<'_>> {
         if let Some((offset, layout)) = self.hashmap.table.table.reallocation(&self.table_layout) {
-            if self.memory.is_empty() {
+            if self.memory.is_empty() || !self.is_memory_aligned() {
                 return None;
             }
             if layout.size() != self.memory.len() {
@@ -23,15 +50,102 @@ impl<S> FrozenHashMap<S> {
             Some(RawBucketIter {
                 base,
                 cur: base,
-                end: unsafe { self.memory.as_ptr().add(self.memory.len()) },
+                // not the end of `memory`: the trailing `Group::WIDTH` control bytes
+                // mirror the leading ones, and have no buckets of their own
+                end: unsafe { base.add(self.hashmap.table.table.buckets()) },
                 bucket_size: self.table_layout.size,
                 items: self.hashmap.table.table.items,
-                _memory: &self.memory,
+                _memory: &self.memory[..],
             })
         } else {
             None
         }
     }
+
+    /// Like `raw_iter`, but starts after the first `skip_full` full buckets.
+    /// Skipped buckets are found by scanning the control bytes only, which is
+    /// cheaper than `raw_iter().skip(n)` for paging through a large map.
+    pub fn raw_iter_from(&self, skip_full: usize) -> Option<RawBucketIter<'_>> {
+        let mut iter = self.raw_iter()?;
+        iter.skip_full(skip_full);
+        Some(iter)
+    }
+
+    /// Like `raw_iter`, but takes the memory along. None for the same reasons.
+    /// A map loaded from a blob may still borrow it; call `into_owned` first to
+    /// get an iterator that does not.
+    pub fn into_raw_iter<'a>(self) -> Option<OwnedRawBucketIter<'a>>
+    where
+        Self: 'a,
+    {
+        let raw = self.raw_iter()?;
+        // moving `memory` moves neither the borrowed slice nor the heap buffer
+        let raw = unsafe { std::mem::transmute::<RawBucketIter<'_>, RawBucketIter<'a>>(raw) };
+        Some(OwnedRawBucketIter {
+            raw,
+            _memory: self.memory,
+        })
+    }
+
+    /// None if `(K, V)` does not fit a bucket.
+    /// `K` and `V` must be the types the map was constructed with.
+    pub fn iter<K, V>(&self) -> Option<TypedBucketIter<'_, K, V>> {
+        self.check_bucket_type::<K, V>().ok()?;
+        Some(TypedBucketIter {
+            raw: self.raw_iter()?,
+            _entry: PhantomData,
+        })
+    }
+
+    /// None if `(K, V)` does not fit a bucket
+    pub(crate) fn typed_iter<'b, K: 'b, V: 'b>(
+        &'b self,
+    ) -> Option<impl Iterator<Item = &'b (K, V)>> {
+        self.check_bucket_type::<K, V>().ok()?;
+        Some(
+            self.raw_iter()?
+                .map(|ptr| unsafe { &*(ptr as *const (K, V)) }),
+        )
+    }
+
+    /// Like `typed_iter`, but yields the entries by value.
+    /// None if `(K, V)` does not fit a bucket.
+    pub fn copied_iter<K: Copy, V: Copy>(&self) -> Option<impl Iterator<Item = (K, V)> + '_> {
+        self.check_bucket_type::<K, V>().ok()?;
+        Some(
+            self.raw_iter()?
+                .map(|ptr| unsafe { core::ptr::read(ptr as *const (K, V)) }),
+        )
+    }
+
+    /// Yields the entries sorted by key. The order of `raw_iter` depends on the
+    /// `RandomState` seed of the process the map was frozen in, so use this to
+    /// compare or diff captures of the same map taken in different processes.
+    /// None if `(K, V)` does not fit a bucket.
+    pub fn canonical_iter<'b, K: Ord + 'b, V: 'b>(
+        &'b self,
+    ) -> Option<impl Iterator<Item = &'b (K, V)>> {
+        let mut entries: Vec<&(K, V)> = self.typed_iter()?.collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        Some(entries.into_iter())
+    }
+}
+
+impl<'a> RawBucketIter<'a> {
+    /// Number of full buckets yet to be yielded
+    pub fn remaining(&self) -> usize {
+        self.items
+    }
+
+    fn skip_full(&mut self, mut n: usize) {
+        while n > 0 && self.items > 0 && self.cur < self.end {
+            if is_full(unsafe { *self.cur }) {
+                self.items -= 1;
+                n -= 1;
+            }
+            self.cur = unsafe { self.cur.add(1) };
+        }
+    }
 }
 
 impl<'a> Iterator for RawBucketIter<'a> {
@@ -43,8 +157,7 @@ impl<'a> Iterator for RawBucketIter<'a> {
             return None;
         }
         while self.cur < self.end {
-            // most significant bit = 0 means bucket is full
-            let full = (unsafe { *self.cur } & 0x80) == 0;
+            let full = is_full(unsafe { *self.cur });
             self.cur = unsafe { self.cur.add(1) };
             if full {
                 let offset = unsafe { self.cur.offset_from(self.base) } * self.bucket_size as isize;
@@ -53,7 +166,7 @@ impl<'a> Iterator for RawBucketIter<'a> {
                 return Some(unsafe { self.base.sub(offset as usize) });
             }
         }
-        return None;
+        None
     }
 
     #[inline]
@@ -61,3 +174,85 @@ impl<'a> Iterator for RawBucketIter<'a> {
         (self.items, Some(self.items))
     }
 }
+
+/// Scans the control bytes from `end` down to `cur`, so both ends together
+/// yield every full bucket once, and never the trailing mirror
+impl DoubleEndedIterator for RawBucketIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.items == 0 {
+            return None;
+        }
+        while self.cur < self.end {
+            self.end = unsafe { self.end.sub(1) };
+            if is_full(unsafe { *self.end }) {
+                let index = unsafe { self.end.offset_from(self.base) } as usize;
+                self.items -= 1;
+                return Some(unsafe { self.base.sub((index + 1) * self.bucket_size) });
+            }
+        }
+        None
+    }
+}
+
+impl ExactSizeIterator for RawBucketIter<'_> {}
+
+/// Once exhausted, `items` stays at 0 or `cur` stays at `end`
+impl std::iter::FusedIterator for RawBucketIter<'_> {}
+
+impl Iterator for OwnedRawBucketIter<'_> {
+    /// memory address of the bucket
+    type Item = *const u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.raw.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.raw.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for OwnedRawBucketIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.raw.next_back()
+    }
+}
+
+impl ExactSizeIterator for OwnedRawBucketIter<'_> {}
+
+impl std::iter::FusedIterator for OwnedRawBucketIter<'_> {}
+
+impl<K, V> Clone for TypedBucketIter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            _entry: PhantomData,
+        }
+    }
+}
+
+impl<'a, K: 'a, V: 'a> Iterator for TypedBucketIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = unsafe { &*(self.raw.next()? as *const (K, V)) };
+        Some((key, value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.raw.size_hint()
+    }
+}
+
+impl<'a, K: 'a, V: 'a> DoubleEndedIterator for TypedBucketIter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (key, value) = unsafe { &*(self.raw.next_back()? as *const (K, V)) };
+        Some((key, value))
+    }
+}
+
+impl<'a, K: 'a, V: 'a> ExactSizeIterator for TypedBucketIter<'a, K, V> {}
+
+impl<'a, K: 'a, V: 'a> std::iter::FusedIterator for TypedBucketIter<'a, K, V> {}
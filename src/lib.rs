@@ -61,6 +61,8 @@
 //! assert_eq!(snapshot, unfrozen_snapshot);
 //! ```
 //!
+//! `freeze` and `thaw` are aliases of `construct` and `reconstruct`, if you prefer `freeze` → `store` → `load` → `thaw`.
+//!
 //! More examples under https://github.com/tyt2y3/frozen-hashbrown/blob/main/tests/unfreeze.rs
 //!
 //! #
@@ -68,11 +70,33 @@
 #[cfg(not(target_pointer_width = "64"))]
 compile_error!("Only support 64-bit platforms");
 
+mod custom_hasher;
+mod error;
+mod extract;
 mod frozen;
+#[cfg(feature = "hashbrown")]
+mod hashbrown_compat;
+#[cfg(feature = "indexmap")]
+mod indexmap_compat;
 mod iter;
+mod lookup;
+mod memory;
+mod minimal;
+mod owned;
+#[cfg(feature = "serde")]
+mod serde_compat;
+mod stream;
 
+pub use error::*;
+pub use extract::*;
 pub use frozen::*;
+#[cfg(feature = "indexmap")]
+pub use indexmap_compat::*;
 pub use iter::*;
+pub use memory::*;
+pub use minimal::*;
+pub use owned::*;
+pub use stream::*;
 
 pub struct Group {}
 
@@ -96,3 +120,21 @@ cfg_if::cfg_if! {
         }
     }
 }
+
+/// Fails to compile if `std::collections::HashMap` does not have the size of
+/// [`HashMap`], i.e. if the toolchain's std is not laid out the way this crate
+/// assumes. Place it anywhere in a crate that reconstructs maps:
+///
+/// ```
+/// frozen_hashbrown::assert_layout_compatible!();
+/// ```
+#[macro_export]
+macro_rules! assert_layout_compatible {
+    () => {
+        const _: () = assert!(
+            ::core::mem::size_of::<$crate::HashMap<$crate::RandomState>>()
+                == ::core::mem::size_of::<::std::collections::HashMap<(), ()>>(),
+            "std::collections::HashMap is not laid out the way frozen-hashbrown expects"
+        );
+    };
+}
@@ -0,0 +1,76 @@
+use crate::{FrozenHashMap, RandomState};
+use std::hash::{BuildHasher, Hash};
+
+impl FrozenHashMap<'_, RandomState> {
+    /// Finds the entry of `key` by probing the control bytes the same way hashbrown
+    /// does, using the seed of the frozen map. None if absent, or if `(K, V)` does
+    /// not fit a bucket.
+    pub(crate) fn find<K: Hash + Eq, V>(&self, key: &K) -> Option<&(K, V)> {
+        self.check_bucket_type::<K, V>().ok()?;
+        let table = &self.hashmap.table.table;
+        let (offset, layout) = table.reallocation(&self.table_layout)?;
+        if layout.size() != self.memory.len() || !self.is_memory_aligned() {
+            return None;
+        }
+        let hasher: std::collections::hash_map::RandomState =
+            unsafe { std::mem::transmute_copy(&self.hashmap.hash_builder) };
+        let hash = hasher.hash_one(key);
+        // the top 7 bits, same as hashbrown's h2
+        let h2 = (hash >> (64 - 7)) as u8 & 0x7f;
+        let width = crate::Group::WIDTH;
+        // includes the trailing mirror, so a group can be loaded at every bucket
+        let ctrl = &self.memory[offset..];
+        let mut pos = hash as usize & table.bucket_mask;
+        let mut stride = 0;
+        // the triangular probe sequence visits every group within this many steps
+        for _ in 0..=table.buckets() / width {
+            let group = &ctrl[pos..pos + width];
+            for (i, c) in group.iter().enumerate() {
+                if *c == h2 {
+                    let index = (pos + i) & table.bucket_mask;
+                    let bucket = offset - (index + 1) * self.table_layout.size;
+                    let entry = unsafe { &*(self.memory[bucket..].as_ptr() as *const (K, V)) };
+                    if entry.0 == *key {
+                        return Some(entry);
+                    }
+                }
+            }
+            if group.contains(&0xFF) {
+                return None;
+            }
+            stride += width;
+            pos = (pos + stride) & table.bucket_mask;
+        }
+        None
+    }
+
+    /// Looks up `key` without reconstructing the map, hashing it with the seed of
+    /// the frozen map like std does. None if absent, or if `(K, V)` does not fit
+    /// a bucket.
+    pub fn get<'b, K: Hash + Eq + 'b, V>(&'b self, key: &K) -> Option<&'b V> {
+        self.find::<K, V>(key).map(|(_, value)| value)
+    }
+
+    pub fn contains_key<K: Hash + Eq, V>(&self, key: &K) -> bool {
+        self.find::<K, V>(key).is_some()
+    }
+
+    /// The value of `key`, or `default` if absent
+    pub fn get_or<'b, K: Hash + Eq + 'b, V>(&'b self, key: &K, default: &'b V) -> &'b V {
+        match self.find::<K, V>(key) {
+            Some((_, value)) => value,
+            None => default,
+        }
+    }
+
+    /// Calls `f` with the value of `key` if present, returns whether it was
+    pub fn contains_then<K: Hash + Eq, V, F: FnOnce(&V)>(&self, key: &K, f: F) -> bool {
+        match self.find::<K, V>(key) {
+            Some((_, value)) => {
+                f(value);
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use core::{alloc::Layout, ptr::NonNull};
+use std::ops::{Deref, DerefMut};
+
+/// The table allocation of a `FrozenHashMap`, either borrowed from a blob or
+/// owned in a buffer aligned to `ctrl_align`
+#[derive(Clone)]
+pub enum Memory<'a> {
+    Borrowed(&'a [u8]),
+    Owned(AlignedBuffer),
+}
+
+/// A heap buffer with a chosen alignment, which a `Vec<u8>` cannot guarantee
+pub struct AlignedBuffer {
+    ptr: NonNull<u8>,
+    len: usize,
+    align: usize,
+}
+
+unsafe impl Send for AlignedBuffer {}
+unsafe impl Sync for AlignedBuffer {}
+
+impl Memory<'_> {
+    /// Copies borrowed memory into a buffer aligned to `align`
+    pub fn into_owned(self, align: usize) -> Memory<'static> {
+        match self {
+            Memory::Borrowed(bytes) => Memory::Owned(AlignedBuffer::new(bytes, align)),
+            Memory::Owned(buffer) => Memory::Owned(buffer),
+        }
+    }
+
+    /// Copies borrowed memory into a buffer aligned to `align` before handing out
+    /// a mutable reference
+    pub fn to_mut(&mut self, align: usize) -> &mut [u8] {
+        if let Memory::Borrowed(bytes) = self {
+            *self = Memory::Owned(AlignedBuffer::new(bytes, align));
+        }
+        match self {
+            Memory::Borrowed(_) => unreachable!(),
+            Memory::Owned(buffer) => buffer,
+        }
+    }
+}
+
+impl Deref for Memory<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Memory::Borrowed(bytes) => bytes,
+            Memory::Owned(buffer) => buffer,
+        }
+    }
+}
+
+impl Default for Memory<'_> {
+    fn default() -> Self {
+        Memory::Borrowed(&[])
+    }
+}
+
+impl AlignedBuffer {
+    /// Copies `bytes` into a new buffer. Panics if `align` is not a power of two.
+    pub fn new(bytes: &[u8], align: usize) -> Self {
+        let layout = Layout::from_size_align(bytes.len(), align).expect("Invalid alignment");
+        let ptr = if layout.size() == 0 {
+            // a dangling but aligned pointer, same as what `Vec` does
+            unsafe { NonNull::new_unchecked(align as *mut u8) }
+        } else {
+            let ptr = unsafe { std::alloc::alloc(layout) };
+            match NonNull::new(ptr) {
+                Some(ptr) => ptr,
+                None => std::alloc::handle_alloc_error(layout),
+            }
+        };
+        unsafe { core::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr.as_ptr(), bytes.len()) };
+        Self {
+            ptr,
+            len: bytes.len(),
+            align,
+        }
+    }
+
+    pub fn align(&self) -> usize {
+        self.align
+    }
+
+    fn layout(&self) -> Layout {
+        unsafe { Layout::from_size_align_unchecked(self.len, self.align) }
+    }
+}
+
+impl Deref for AlignedBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl DerefMut for AlignedBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl Clone for AlignedBuffer {
+    fn clone(&self) -> Self {
+        Self::new(self, self.align)
+    }
+}
+
+impl Drop for AlignedBuffer {
+    fn drop(&mut self) {
+        if self.len != 0 {
+            unsafe { std::alloc::dealloc(self.ptr.as_ptr(), self.layout()) };
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{
+    frozen::borrow_if_aligned, FrozenHashMap, HashMap, RawTable, RawTableInner, TableLayout,
+};
+use core::ptr::NonNull;
+
+/// Every blob stored by `FrozenHashMap::store_minimal` starts with these bytes
+pub const MINIMAL_MAGIC: [u8; 4] = *b"FHBR";
+
+/// Size of everything before `memory` in a minimal blob
+const MINIMAL_HEADER_LEN: usize = MINIMAL_MAGIC.len() + 4 * 8;
+
+impl<S> FrozenHashMap<'_, S> {
+    /// A compact format for iteration only: `bucket_mask`, `items`, the table
+    /// layout and `memory`, without the hasher seed, the type fingerprint or any
+    /// version and ABI information. The header is 36 bytes, about a third of that
+    /// of `store`, which matters when capturing many small maps.
+    /// All integers are little-endian.
+    pub fn store_minimal(&self) -> Vec<u8> {
+        let table = &self.hashmap.table.table;
+        let mut bytes = Vec::with_capacity(MINIMAL_HEADER_LEN + self.memory.len());
+        bytes.extend_from_slice(&MINIMAL_MAGIC);
+        bytes.extend_from_slice(&(table.bucket_mask as u64).to_le_bytes());
+        bytes.extend_from_slice(&(table.items as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.table_layout.size as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.table_layout.ctrl_align as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.memory);
+        bytes
+    }
+}
+
+impl<'a> FrozenHashMap<'a, ()> {
+    /// Reverse of `store_minimal`. Without a seed the map cannot be reconstructed or
+    /// looked up, so the hasher is `()`, which only leaves the iterators and
+    /// `dump_text`. `growth_left` is recomputed from the control bytes.
+    /// The length of `memory` is implied by the table layout, and must match.
+    pub fn load_minimal(bytes: &'a [u8]) -> Option<Self> {
+        let mut cursor: usize = 0;
+        let mut take = |len: usize| -> Option<&'a [u8]> {
+            let chunk = bytes.get(cursor..cursor.checked_add(len)?)?;
+            cursor += len;
+            Some(chunk)
+        };
+        let read_usize =
+            |chunk: &[u8]| usize::try_from(u64::from_le_bytes(chunk.try_into().unwrap()));
+        if take(MINIMAL_MAGIC.len())? != MINIMAL_MAGIC {
+            return None;
+        }
+        let bucket_mask = read_usize(take(8)?).ok()?;
+        let items = read_usize(take(8)?).ok()?;
+        let table_layout = TableLayout {
+            size: read_usize(take(8)?).ok()?,
+            ctrl_align: read_usize(take(8)?).ok()?,
+        };
+        let buckets = bucket_mask.checked_add(1)?;
+        if !table_layout.is_valid() || !buckets.is_power_of_two() {
+            return None;
+        }
+        let memory = &bytes[MINIMAL_HEADER_LEN..];
+        let mut table = RawTableInner {
+            bucket_mask,
+            ctrl: NonNull::dangling(),
+            growth_left: 0,
+            items,
+        };
+        if table.is_empty_singleton() {
+            if !memory.is_empty() || items != 0 {
+                return None;
+            }
+        } else {
+            let (layout, offset) = table_layout.calculate_layout_for(buckets)?;
+            if layout.size() != memory.len() || items > table.bucket_capacity() {
+                return None;
+            }
+            let deleted = memory[offset..offset + buckets]
+                .iter()
+                .filter(|c| **c == 0x80)
+                .count();
+            table.growth_left = table.bucket_capacity().checked_sub(items + deleted)?;
+        }
+        Some(Self {
+            table_layout,
+            hashmap: HashMap {
+                hash_builder: (),
+                table: RawTable { table },
+            },
+            memory: borrow_if_aligned(memory, table_layout.memory_align()),
+            type_fingerprint: None,
+        })
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{FrozenHashMap, RandomState};
+use std::hash::Hash;
+
+/// Every blob stored by `FrozenOwnedHashMap::store_with` starts with these bytes
+pub const OWNED_MAGIC: [u8; 4] = *b"FHBO";
+
+/// A deep copy of a map's entries. Unlike `FrozenHashMap`, this captures keys and
+/// values that own heap memory (e.g. `String`), at the cost of cloning every entry
+/// and rehashing on reconstruct.
+#[derive(Debug, Clone)]
+pub struct FrozenOwnedHashMap<K, V> {
+    pub hash_builder: RandomState,
+    /// In the iteration order of the source map
+    pub entries: Vec<(K, V)>,
+}
+
+impl FrozenHashMap<'_, RandomState> {
+    pub fn construct_cloning<K: Clone, V: Clone>(
+        hashmap: &std::collections::HashMap<K, V>,
+    ) -> FrozenOwnedHashMap<K, V> {
+        FrozenOwnedHashMap {
+            hash_builder: unsafe { std::mem::transmute_copy(hashmap.hasher()) },
+            entries: hashmap
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl<K, V> FrozenOwnedHashMap<K, V> {
+    /// Rebuilds a map with the same hasher as the source map. Entries are
+    /// re-inserted, so the iteration order may differ.
+    pub fn reconstruct(&self) -> std::collections::HashMap<K, V>
+    where
+        K: Hash + Eq + Clone,
+        V: Clone,
+    {
+        let mut hashmap =
+            std::collections::HashMap::with_capacity_and_hasher(self.entries.len(), self.hasher());
+        hashmap.extend(self.entries.iter().cloned());
+        hashmap
+    }
+
+    /// Same as `reconstruct`, but moves the entries instead of cloning them
+    pub fn into_hashmap(self) -> std::collections::HashMap<K, V>
+    where
+        K: Hash + Eq,
+    {
+        let mut hashmap =
+            std::collections::HashMap::with_capacity_and_hasher(self.entries.len(), self.hasher());
+        hashmap.extend(self.entries);
+        hashmap
+    }
+
+    fn hasher(&self) -> std::collections::hash_map::RandomState {
+        unsafe { std::mem::transmute_copy(&self.hash_builder) }
+    }
+
+    /// Serializes the hasher and every entry, encoding each entry with `encode`.
+    /// The blob is self-contained, all integers are little-endian.
+    pub fn store_with(&self, mut encode: impl FnMut(&K, &V, &mut Vec<u8>)) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&OWNED_MAGIC);
+        bytes.extend_from_slice(&self.hash_builder.k0.to_le_bytes());
+        bytes.extend_from_slice(&self.hash_builder.k1.to_le_bytes());
+        bytes.extend_from_slice(&(self.entries.len() as u64).to_le_bytes());
+        let mut entry = Vec::new();
+        for (key, value) in self.entries.iter() {
+            entry.clear();
+            encode(key, value, &mut entry);
+            bytes.extend_from_slice(&(entry.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&entry);
+        }
+        bytes
+    }
+
+    /// Reverse of `store_with`. None means failed to load, including when
+    /// `decode` fails on any entry.
+    pub fn load_with(
+        bytes: &[u8],
+        mut decode: impl FnMut(&[u8]) -> Option<(K, V)>,
+    ) -> Option<Self> {
+        let mut cursor: usize = 0;
+        let mut take = |len: usize| -> Option<&[u8]> {
+            let chunk = bytes.get(cursor..cursor.checked_add(len)?)?;
+            cursor += len;
+            Some(chunk)
+        };
+        let read_u64 = |chunk: &[u8]| u64::from_le_bytes(chunk.try_into().unwrap());
+        if take(OWNED_MAGIC.len())? != OWNED_MAGIC {
+            return None;
+        }
+        let hash_builder = RandomState {
+            k0: read_u64(take(8)?),
+            k1: read_u64(take(8)?),
+        };
+        let count = usize::try_from(read_u64(take(8)?)).ok()?;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let len = usize::try_from(read_u64(take(8)?)).ok()?;
+            entries.push(decode(take(len)?)?);
+        }
+        if take(1).is_some() {
+            return None;
+        }
+        Some(Self {
+            hash_builder,
+            entries,
+        })
+    }
+}
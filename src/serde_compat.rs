@@ -0,0 +1,129 @@
+use crate::{
+    frozen::validate_table, AlignedBuffer, FrozenHashMap, HashMap, Memory, RandomState, RawTable,
+    RawTableInner, TableLayout,
+};
+use core::{fmt, ptr::NonNull};
+use serde::{
+    de::{Error, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+/// What goes on the wire: the table layout, the scalar fields of the map and the
+/// memory blob. `ctrl` is a pointer into the memory of the producer, so it is left
+/// out and pointed into `memory` again on reconstruct.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "FrozenHashMap")]
+struct Wire<M> {
+    size: usize,
+    ctrl_align: usize,
+    k0: u64,
+    k1: u64,
+    bucket_mask: usize,
+    growth_left: usize,
+    items: usize,
+    type_fingerprint: Option<u64>,
+    memory: M,
+}
+
+/// `memory` on the way out, written with `serialize_bytes` so binary formats
+/// store it as one blob instead of a sequence of `u8`s
+struct Bytes<'a>(&'a [u8]);
+
+impl Serialize for Bytes<'_> {
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// `memory` on the way in, read with `deserialize_byte_buf`. Formats without a
+/// bytes type, e.g. JSON, hand over a sequence of `u8`s instead.
+struct ByteBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ByteBuf {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_byte_buf(ByteBufVisitor)
+    }
+}
+
+struct ByteBufVisitor;
+
+impl<'de> Visitor<'de> for ByteBufVisitor {
+    type Value = ByteBuf;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a byte array")
+    }
+
+    fn visit_bytes<E: Error>(self, bytes: &[u8]) -> Result<ByteBuf, E> {
+        Ok(ByteBuf(bytes.to_vec()))
+    }
+
+    fn visit_byte_buf<E: Error>(self, bytes: Vec<u8>) -> Result<ByteBuf, E> {
+        Ok(ByteBuf(bytes))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<ByteBuf, A::Error> {
+        // the hint comes from the input, so do not trust it with a large allocation
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(ByteBuf(bytes))
+    }
+}
+
+/// Like `store`, the memory is in the byte order and layout of the producer, so it
+/// can only be reconstructed on a compatible platform.
+impl Serialize for FrozenHashMap<'_, RandomState> {
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        let table = &self.hashmap.table.table;
+        Wire {
+            size: self.table_layout.size,
+            ctrl_align: self.table_layout.ctrl_align,
+            k0: self.hashmap.hash_builder.k0,
+            k1: self.hashmap.hash_builder.k1,
+            bucket_mask: table.bucket_mask,
+            growth_left: table.growth_left,
+            items: table.items,
+            type_fingerprint: self.type_fingerprint,
+            memory: Bytes(&self.memory[..]),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Validates the table the same way `load` does. The memory is copied into a
+/// buffer aligned to `ctrl_align`.
+impl<'de> Deserialize<'de> for FrozenHashMap<'static, RandomState> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = Wire::<ByteBuf>::deserialize(deserializer)?;
+        let memory = wire.memory.0;
+        let table_layout = TableLayout {
+            size: wire.size,
+            ctrl_align: wire.ctrl_align,
+        };
+        let table = RawTableInner {
+            bucket_mask: wire.bucket_mask,
+            ctrl: NonNull::dangling(),
+            growth_left: wire.growth_left,
+            items: wire.items,
+        };
+        validate_table(&table_layout, &table, memory.len()).map_err(D::Error::custom)?;
+        Ok(Self {
+            table_layout,
+            hashmap: HashMap {
+                hash_builder: RandomState {
+                    k0: wire.k0,
+                    k1: wire.k1,
+                },
+                table: RawTable { table },
+            },
+            memory: if memory.is_empty() {
+                Memory::default()
+            } else {
+                Memory::Owned(AlignedBuffer::new(&memory, table_layout.memory_align()))
+            },
+            type_fingerprint: wire.type_fingerprint,
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{
+    header_len, read_header, FrozenHashMap, LoadError, RandomState, TableLayout, HEADER_PREFIX_LEN,
+};
+use std::io::{self, Read};
+
+/// Metadata of one blob in a stream, see `FrozenHashMap::iter_blobs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlobInfo {
+    pub table_layout: TableLayout,
+    pub items: usize,
+    pub buckets: usize,
+    pub memory_len: usize,
+    pub type_fingerprint: Option<u64>,
+}
+
+/// Iterator over concatenated blobs, see `FrozenHashMap::iter_blobs`
+pub struct BlobIter<R> {
+    reader: R,
+    done: bool,
+}
+
+impl FrozenHashMap<'_, RandomState> {
+    /// Reads concatenated blobs, as written by `store`, one after another. Only the
+    /// header of each blob is validated, `memory` is skipped without being loaded.
+    /// The iterator ends after the last blob, or after the first error.
+    pub fn iter_blobs<R: Read>(reader: R) -> BlobIter<R> {
+        BlobIter {
+            reader,
+            done: false,
+        }
+    }
+}
+
+impl<R: Read> BlobIter<R> {
+    fn next_blob(&mut self) -> Result<Option<BlobInfo>, LoadError> {
+        let mut header = vec![0u8; HEADER_PREFIX_LEN];
+        if !self.read_full(&mut header, true)? {
+            return Ok(None);
+        }
+        let prefix_len = header.len();
+        header.resize(header_len(&header)?, 0);
+        self.read_full(&mut header[prefix_len..], false)?;
+        let (header, _) = read_header(&header)?;
+        header.validate()?;
+        let memory_len = header.length.ok_or(LoadError::TruncatedBlob)?;
+        let skipped = io::copy(
+            &mut self.reader.by_ref().take(memory_len as u64),
+            &mut io::sink(),
+        )
+        .map_err(|err| LoadError::Io(err.kind()))?;
+        if skipped != memory_len as u64 {
+            return Err(LoadError::TruncatedBlob);
+        }
+        let table = &header.table;
+        Ok(Some(BlobInfo {
+            table_layout: header.table_layout,
+            items: table.items,
+            buckets: table.bucket_mask + 1,
+            memory_len,
+            type_fingerprint: header.type_fingerprint,
+        }))
+    }
+}
+
+impl<R: Read> BlobIter<R> {
+    /// Fills `buf`, false if the reader is at its end and `eof_ok`
+    fn read_full(&mut self, buf: &mut [u8], eof_ok: bool) -> Result<bool, LoadError> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match self.reader.read(&mut buf[filled..]) {
+                Ok(0) if filled == 0 && eof_ok => return Ok(false),
+                Ok(0) => return Err(LoadError::TruncatedBlob),
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(LoadError::Io(err.kind())),
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: Read> Iterator for BlobIter<R> {
+    type Item = Result<BlobInfo, LoadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = self.next_blob().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
@@ -0,0 +1,45 @@
+#![cfg(unix)]
+
+use anyhow::{Context, Result};
+use frozen_hashbrown::FrozenHashMap;
+use std::collections::HashMap;
+
+#[test]
+fn reconstruct_in_forked_child() -> Result<()> {
+    let map: HashMap<u32, char> = (0..26).map(|v| (v, (b'a' + v as u8) as char)).collect();
+    let snapshot = format!("{map:?}");
+
+    let frozen = FrozenHashMap::construct(&map);
+    let stored: Vec<u8> = frozen.store();
+
+    let pid = unsafe { libc::fork() };
+    assert!(pid >= 0, "fork failed");
+    if pid == 0 {
+        let child = || -> Option<bool> {
+            // the same addresses, in a copy of the parent's address space
+            let mut frozen = frozen.clone();
+            let same = format!("{:?}", frozen.reconstruct::<u32, char>()?) == snapshot;
+            // a buffer allocated by the child, at a different address
+            let mut loaded = FrozenHashMap::load_owned(&stored).ok()?;
+            let moved = format!("{:?}", loaded.reconstruct::<u32, char>()?) == snapshot;
+            let iterated = loaded.copied_iter::<u32, char>()?.count() == map.len();
+            Some(same && moved && iterated)
+        };
+        let ok = std::panic::catch_unwind(child).ok().flatten() == Some(true);
+        unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+    }
+
+    let mut status = 0;
+    let waited = unsafe { libc::waitpid(pid, &mut status, 0) };
+    assert_eq!(waited, pid);
+    assert!(libc::WIFEXITED(status), "child did not exit");
+    assert_eq!(libc::WEXITSTATUS(status), 0, "child failed to reconstruct");
+
+    let mut unfrozen = FrozenHashMap::load(&stored).context("Failed to load")?;
+    let unfrozen = unfrozen
+        .reconstruct::<u32, char>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "hashbrown")]
+
+use anyhow::{Context, Result};
+use frozen_hashbrown::FrozenHashMap;
+use std::collections::hash_map::RandomState;
+
+#[test]
+fn unfreeze_hashbrown_map() -> Result<()> {
+    let mut map: hashbrown::HashMap<char, i32, RandomState> = Default::default();
+    map.extend([('a', 1), ('b', 2), ('c', 3), ('d', 4)]);
+    let snapshot = format!("{map:?}");
+    println!("{snapshot}");
+
+    let frozen = FrozenHashMap::construct_from_hashbrown(&map);
+    std::mem::drop(map);
+    let frozen: Vec<u8> = frozen.store();
+
+    let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    let unfrozen = unfrozen
+        .reconstruct_hashbrown::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+    assert_eq!(unfrozen.get(&'c'), Some(&3));
+
+    Ok(())
+}
+
+#[test]
+fn unfreeze_hashbrown_set() -> Result<()> {
+    let mut set: hashbrown::HashSet<u64, RandomState> = Default::default();
+    set.extend(0..1000u64);
+    let snapshot = format!("{set:?}");
+
+    let frozen = FrozenHashMap::construct_from_hashbrown_set(&set);
+    std::mem::drop(set);
+    let frozen: Vec<u8> = frozen.store();
+
+    let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    let unfrozen = unfrozen
+        .reconstruct_hashbrown_set::<u64>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+    assert!(unfrozen.contains(&999));
+    assert!(!unfrozen.contains(&1000));
+
+    Ok(())
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "indexmap")]
+
+use anyhow::{Context, Result};
+use frozen_hashbrown::FrozenIndexMap;
+use indexmap::IndexMap;
+
+#[test]
+fn unfreeze_indexmap() -> Result<()> {
+    let mut map: IndexMap<u8, (i64, &str)> = IndexMap::new();
+    for (i, word) in ["zebra", "apple", "mango", "kiwi", "banana"]
+        .into_iter()
+        .enumerate()
+    {
+        map.insert(b'z' - i as u8, (-(i as i64), word));
+    }
+    map.shift_remove(&b'x');
+    let snapshot = format!("{map:?}");
+    println!("{snapshot}");
+
+    let frozen = FrozenIndexMap::construct_indexmap(&map);
+    std::mem::drop(map);
+    println!("{frozen:?}");
+    let frozen: Vec<u8> = frozen.store();
+
+    let unfrozen = FrozenIndexMap::load(&frozen).context("Failed to load")?;
+    assert!(unfrozen.iter::<u16, (i64, &str)>().is_none());
+    let unfrozen = unfrozen
+        .reconstruct_indexmap::<u8, (i64, &str)>()
+        .context("Failed to reconstruct")?;
+
+    // insertion order holds
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+    assert_eq!(unfrozen.get(&b'v'), Some(&(-4, "banana")));
+    assert_eq!(unfrozen.get_index_of(&b'w'), Some(2));
+
+    Ok(())
+}
+
+#[test]
+fn unfreeze_empty_indexmap() -> Result<()> {
+    let map: IndexMap<u32, u32> = IndexMap::new();
+
+    let frozen = FrozenIndexMap::construct_indexmap(&map).store();
+    let unfrozen = FrozenIndexMap::load(&frozen).context("Failed to load")?;
+    let unfrozen = unfrozen
+        .reconstruct_indexmap::<u32, u32>()
+        .context("Failed to reconstruct")?;
+    assert!(unfrozen.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn reconstruct_indexmap_borrowed() -> Result<()> {
+    let map: IndexMap<u32, u64> = (0..100).rev().map(|v| (v, v as u64 * 3)).collect();
+    let snapshot = format!("{map:?}");
+
+    let frozen = FrozenIndexMap::construct_indexmap(&map);
+    std::mem::drop(map);
+    // the index table is dumped along with the entries
+    assert_eq!(frozen.indices.len(), 100);
+    let stored: Vec<u8> = frozen.store();
+
+    let unfrozen = FrozenIndexMap::load(&stored).context("Failed to load")?;
+    assert!(unfrozen.reconstruct::<u32, u32>().is_none());
+    let unfrozen = unfrozen
+        .reconstruct::<u32, u64>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{:?}", *unfrozen));
+    // lookups go through the index table as loaded
+    assert_eq!(unfrozen.get(&7), Some(&21));
+    assert_eq!(unfrozen.get_index_of(&99), Some(0));
+    assert_eq!(unfrozen.get(&100), None);
+
+    // without the index table
+    let end = stored.len() - frozen.indices.store().len();
+    assert!(FrozenIndexMap::load(&stored[..end]).is_none());
+
+    // an index past the entries
+    let mut frozen = frozen;
+    let bucket = frozen.indices.raw_iter().context("No iter")?.next();
+    let offset = bucket.context("No bucket")? as usize - frozen.indices.memory.as_ptr() as usize;
+    let align = frozen.indices.table_layout.ctrl_align;
+    let index =
+        &mut frozen.indices.memory.to_mut(align)[offset..offset + std::mem::size_of::<usize>()];
+    assert!(usize::from_ne_bytes(index.try_into()?) < 100);
+    index.copy_from_slice(&usize::MAX.to_ne_bytes());
+    assert!(frozen.reconstruct::<u32, u64>().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn iter_rejects_corrupt_entry_layout() -> Result<()> {
+    use frozen_hashbrown::{AlignedBuffer, Memory};
+
+    let map: IndexMap<u32, u64> = (0..10).map(|v| (v, v as u64)).collect();
+    let frozen = FrozenIndexMap::construct_indexmap(&map);
+    assert!(frozen.iter::<u32, u64>().is_some());
+
+    // the `u64`s would be misaligned
+    let mut corrupt = frozen.clone();
+    corrupt.entry_layout.align = 1;
+    assert!(corrupt.iter::<u32, u64>().is_none());
+
+    // the entries would not stay aligned one after another
+    let mut corrupt = frozen.clone();
+    corrupt.entry_layout.size += 4;
+    corrupt.memory = Memory::Owned(AlignedBuffer::new(
+        &vec![0; corrupt.entry_layout.size * corrupt.len],
+        corrupt.entry_layout.align,
+    ));
+    assert!(corrupt.iter::<u32, u64>().is_none());
+
+    // the value would end past the entry
+    let mut corrupt = frozen;
+    corrupt.entry_layout.value_offset = corrupt.entry_layout.size;
+    assert!(corrupt.iter::<u32, u64>().is_none());
+
+    Ok(())
+}
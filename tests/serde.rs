@@ -0,0 +1,79 @@
+#![cfg(feature = "serde")]
+
+use anyhow::{Context, Result};
+use frozen_hashbrown::FrozenHashMap;
+use std::collections::HashMap;
+
+#[test]
+fn serde_round_trip() -> Result<()> {
+    let map: HashMap<char, i32> = ('a'..='z').zip(0..).collect();
+    let snapshot = format!("{map:?}");
+
+    let frozen = FrozenHashMap::construct(&map);
+    std::mem::drop(map);
+
+    let json = serde_json::to_string(&frozen)?;
+    let mut unfrozen: FrozenHashMap = serde_json::from_str(&json)?;
+    assert_eq!(unfrozen, frozen);
+    let unfrozen = unfrozen
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    let encoded = bincode::serialize(&frozen)?;
+    // the memory is one length-prefixed blob at the end
+    assert!(encoded.ends_with(&frozen.memory));
+    let memory_len = &encoded[encoded.len() - frozen.memory.len() - 8..][..8];
+    assert_eq!(memory_len, (frozen.memory.len() as u64).to_le_bytes());
+    let mut unfrozen: FrozenHashMap = bincode::deserialize(&encoded)?;
+    let unfrozen = unfrozen
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    let empty = FrozenHashMap::construct(&HashMap::<char, i32>::new());
+    let mut unfrozen: FrozenHashMap = serde_json::from_str(&serde_json::to_string(&empty)?)?;
+    assert!(unfrozen
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn serde_rejects_invalid_table() -> Result<()> {
+    let map: HashMap<char, i32> = ('a'..='z').zip(0..).collect();
+    let frozen = FrozenHashMap::construct(&map);
+    let json: serde_json::Value = serde_json::to_value(&frozen)?;
+
+    let mut invalid = json.clone();
+    invalid["bucket_mask"] = (frozen.bucket_capacity() as u64).into();
+    let err = serde_json::from_value::<FrozenHashMap>(invalid).unwrap_err();
+    assert!(err.to_string().contains("power of two"), "{err}");
+
+    // `bucket_mask + 1` would overflow
+    let mut invalid = json.clone();
+    invalid["bucket_mask"] = u64::MAX.into();
+    let err = serde_json::from_value::<FrozenHashMap>(invalid).unwrap_err();
+    assert!(err.to_string().contains("power of two"), "{err}");
+
+    // below `Group::WIDTH`, so std could not load the control bytes aligned
+    let mut invalid = json.clone();
+    invalid["ctrl_align"] = 1.into();
+    let err = serde_json::from_value::<FrozenHashMap>(invalid).unwrap_err();
+    assert!(err.to_string().contains("Invalid table layout"), "{err}");
+
+    let mut invalid = json.clone();
+    invalid["memory"]
+        .as_array_mut()
+        .context("Not an array")?
+        .pop();
+    assert!(serde_json::from_value::<FrozenHashMap>(invalid).is_err());
+
+    let mut invalid = json;
+    invalid["items"] = 1_000_000.into();
+    assert!(serde_json::from_value::<FrozenHashMap>(invalid).is_err());
+
+    Ok(())
+}
@@ -1,3 +1,10 @@
+// the baseline tests predate the lint gate
+#![allow(
+    clippy::iter_kv_map,
+    clippy::useless_format,
+    clippy::transmute_ptr_to_ref
+)]
+
 use anyhow::{Context, Result};
 use frozen_hashbrown::FrozenHashMap;
 use std::{
@@ -5,6 +12,8 @@ use std::{
     fmt::{Debug, Write},
 };
 
+frozen_hashbrown::assert_layout_compatible!();
+
 #[test]
 fn unfreeze() -> Result<()> {
     let map: HashMap<char, i32> = [('a', 1), ('b', 2), ('c', 3), ('d', 4)]
@@ -78,6 +87,53 @@ fn unfreeze_large_set() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn unfreeze_hashset() -> Result<()> {
+    use std::collections::HashSet;
+
+    let set: HashSet<i32> = (1..=1000).collect();
+    let snapshot = format!("{set:?}");
+
+    let frozen = FrozenHashMap::construct_set(&set);
+    std::mem::drop(set);
+    let frozen: Vec<u8> = frozen.store();
+
+    let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    let unfrozen = unfrozen
+        .reconstruct_set::<i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+    assert!(unfrozen.contains(&500));
+    assert!(!unfrozen.contains(&1001));
+
+    let set: HashSet<String> = ["apple", "banana", "cherry", "dragonfruit"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+    let snapshot = format!("{set:?}");
+
+    let mut frozen = FrozenHashMap::construct_set(&set);
+    // the strings live on the heap, so only the table can go away
+    std::mem::forget(set);
+    let unfrozen = frozen
+        .reconstruct_set::<String>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+    assert!(unfrozen.contains("cherry"));
+
+    // a zero-sized element takes no space in a bucket
+    let set: HashSet<()> = [()].into_iter().collect();
+    let mut frozen = FrozenHashMap::construct_set(&set);
+    std::mem::drop(set);
+    let unfrozen = frozen
+        .reconstruct_set::<()>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(unfrozen.len(), 1);
+    assert!(unfrozen.contains(&()));
+
+    Ok(())
+}
+
 #[test]
 fn unfreeze_u128() -> Result<()> {
     let map: HashMap<u32, u128> = [
@@ -236,3 +292,1828 @@ fn unfreeze_raw_iter_generic_4() {
     .collect();
     unfreeze_raw_iter_generic(map).unwrap();
 }
+
+#[test]
+fn load_rejects_data_for_empty_singleton() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<char, i32> = HashMap::new();
+
+    let frozen = FrozenHashMap::construct(&map);
+    assert!(frozen.memory.is_empty());
+    let mut frozen: Vec<u8> = frozen.store();
+    assert!(FrozenHashMap::load(&frozen).is_ok());
+
+    // the length prefix is the trailing 8 bytes when there is no memory
+    let cursor = frozen.len() - 8;
+    frozen[cursor..].copy_from_slice(&4u64.to_le_bytes());
+    frozen.extend_from_slice(&[0xFF; 4]);
+    assert_eq!(
+        FrozenHashMap::load(&frozen).unwrap_err(),
+        LoadError::MemorySizeMismatch {
+            expected: 0,
+            found: 4
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn raw_iter_stops_before_ctrl_mirror() -> Result<()> {
+    let width = frozen_hashbrown::Group::WIDTH;
+    // a new seed every time, until bucket 0, whose control byte is mirrored, is full
+    let mut frozen = (0..100)
+        .map(|_| {
+            let map: HashMap<u32, u32> = [(1, 10), (2, 20), (3, 30)].into_iter().collect();
+            FrozenHashMap::construct(&map)
+        })
+        .find(|frozen| {
+            let buckets = frozen.hashmap.table.table.bucket_mask + 1;
+            frozen.memory[frozen.memory.len() - buckets - width] & 0x80 == 0
+        })
+        .context("Bucket 0 never full")?;
+    let buckets = frozen.hashmap.table.table.bucket_mask + 1;
+    assert_eq!(buckets, 4);
+
+    // claim one more item than there are full buckets, so only the bound stops
+    // the iterator from reading the mirror of bucket 0 as a fifth bucket
+    frozen.hashmap.table.table.items += 1;
+    let start = frozen.memory.as_ptr() as usize;
+    let end = start + frozen.memory.len();
+    let mut buckets: Vec<usize> = frozen
+        .raw_iter()
+        .context("No iter")?
+        .map(|ptr| ptr as usize)
+        .collect();
+    assert_eq!(buckets.len(), 3);
+    assert!(buckets.iter().all(|b| (start..end).contains(b)));
+    buckets.sort_unstable();
+    buckets.dedup();
+    assert_eq!(buckets.len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn unfreeze_raw_iter_from() -> Result<()> {
+    let map: HashMap<i32, i32> = (0..1000).map(|v| (v, v * 2)).collect();
+
+    let frozen = FrozenHashMap::construct(&map);
+    let frozen: Vec<u8> = frozen.store();
+    let unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+
+    let all: Vec<*const u8> = unfrozen.raw_iter().context("No iter")?.collect();
+    for (skip, take) in [(0, 50), (100, 50), (980, 50), (1000, 10), (2000, 10)] {
+        let page: Vec<*const u8> = unfrozen
+            .raw_iter_from(skip)
+            .context("No iter")?
+            .take(take)
+            .collect();
+        let expected: Vec<*const u8> = all.iter().copied().skip(skip).take(take).collect();
+        assert_eq!(page, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn try_construct_unsupported_hasher() -> Result<()> {
+    use frozen_hashbrown::ConstructError;
+    use std::hash::BuildHasherDefault;
+
+    let map: HashMap<char, i32> = [('a', 1), ('b', 2)].into_iter().collect();
+    let frozen = FrozenHashMap::try_construct(&map)?;
+    assert_eq!(frozen.len(), 2);
+
+    #[derive(Default)]
+    struct Fnv(u64);
+    impl std::hash::Hasher for Fnv {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+        fn write(&mut self, bytes: &[u8]) {
+            for b in bytes {
+                self.0 = (self.0 ^ *b as u64).wrapping_mul(0x100000001b3);
+            }
+        }
+    }
+    let mut map: HashMap<char, i32, BuildHasherDefault<Fnv>> = Default::default();
+    map.insert('a', 1);
+    assert!(matches!(
+        FrozenHashMap::try_construct(&map),
+        Err(ConstructError::UnsupportedHasher(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn load_borrowed_and_owned() -> Result<()> {
+    use frozen_hashbrown::Memory;
+
+    let map: HashMap<char, i32> = [('a', 1), ('b', 2), ('c', 3), ('d', 4)]
+        .into_iter()
+        .collect();
+    let snapshot = format!("{map:?}");
+
+    let frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+
+    // memory can only be borrowed if it happens to be aligned in the buffer
+    let mut buffer = vec![0u8; frozen.len() + 64];
+    let offset = (0..64)
+        .find(|o| {
+            buffer[*o..*o + frozen.len()].copy_from_slice(&frozen);
+            matches!(
+                FrozenHashMap::load(&buffer[*o..*o + frozen.len()]),
+                Ok(FrozenHashMap {
+                    memory: Memory::Borrowed(_),
+                    ..
+                })
+            )
+        })
+        .context("No aligned offset")?;
+    let mut borrowed =
+        FrozenHashMap::load(&buffer[offset..offset + frozen.len()]).context("Failed to load")?;
+    let unfrozen = borrowed
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    let mut owned = FrozenHashMap::load_owned(&frozen).context("Failed to load")?;
+    std::mem::drop(frozen);
+    assert!(matches!(owned.memory, Memory::Owned(_)));
+    let unfrozen = owned
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    Ok(())
+}
+
+fn assert_aligned<K, V>(frozen: &FrozenHashMap) -> Result<()> {
+    let align = std::mem::align_of::<(K, V)>();
+    assert_eq!(
+        frozen.memory.as_ptr() as usize % frozen.table_layout.ctrl_align,
+        0
+    );
+    for ptr in frozen.raw_iter().context("No iter")? {
+        assert_eq!(ptr as usize % align, 0);
+    }
+    Ok(())
+}
+
+#[test]
+fn memory_aligned_to_ctrl_align() -> Result<()> {
+    use frozen_hashbrown::Memory;
+
+    let map: HashMap<u32, u128> = (0..100).map(|v| (v, v as u128)).collect();
+    let frozen = FrozenHashMap::construct(&map);
+    assert_aligned::<u32, u128>(&frozen)?;
+    let stored = frozen.store();
+    let mut buffer = vec![0u8; stored.len() + 32];
+    for offset in 0..32 {
+        buffer[offset..offset + stored.len()].copy_from_slice(&stored);
+        let loaded = FrozenHashMap::load(&buffer[offset..offset + stored.len()])?;
+        assert_aligned::<u32, u128>(&loaded)?;
+    }
+
+    let map: HashMap<u8, (i64, i32)> = (0..100).map(|v| (v, (-(v as i64), v as i32))).collect();
+    let frozen = FrozenHashMap::construct(&map);
+    assert_aligned::<u8, (i64, i32)>(&frozen.clone())?;
+    assert_aligned::<u8, (i64, i32)>(&FrozenHashMap::load_owned(&frozen.store())?)?;
+
+    // misaligned memory is refused rather than dereferenced
+    let mut buffer = vec![0u8; frozen.memory.len() + 1];
+    let misaligned = if buffer.as_ptr() as usize % 2 == 0 {
+        1
+    } else {
+        0
+    };
+    buffer[misaligned..misaligned + frozen.memory.len()].copy_from_slice(&frozen.memory);
+    let mut frozen = frozen;
+    frozen.memory = Memory::Borrowed(&buffer[misaligned..misaligned + frozen.memory.len()]);
+    assert!(frozen.raw_iter().is_none());
+    assert!(frozen.get::<u8, (i64, i32)>(&1).is_none());
+    assert!(frozen.reconstruct::<u8, (i64, i32)>().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn load_borrowed() -> Result<()> {
+    use frozen_hashbrown::{LoadError, Memory};
+
+    let map: HashMap<char, i32> = ('a'..='z').zip(0..).collect();
+    let snapshot = format!("{map:?}");
+
+    let frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+    let memory_len = FrozenHashMap::load(&frozen)?.memory.len();
+    let align = FrozenHashMap::load(&frozen)?.table_layout.ctrl_align;
+
+    let mut buffer = vec![0u8; frozen.len() + 64];
+    let offset = (0..64)
+        .find(|o| (buffer[*o..].as_ptr() as usize + frozen.len() - memory_len) % align == 0)
+        .context("No aligned offset")?;
+    buffer[offset..offset + frozen.len()].copy_from_slice(&frozen);
+    let blob = &buffer[offset..offset + frozen.len()];
+    let mut borrowed = FrozenHashMap::load_borrowed(blob)?;
+    match &borrowed.memory {
+        Memory::Borrowed(memory) => {
+            assert_eq!(memory.as_ptr(), blob[blob.len() - memory_len..].as_ptr())
+        }
+        Memory::Owned(_) => panic!("Memory was copied"),
+    }
+    let unfrozen = borrowed
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    let misaligned = offset + 1;
+    buffer[misaligned..misaligned + frozen.len()].copy_from_slice(&frozen);
+    assert_eq!(
+        FrozenHashMap::load_borrowed(&buffer[misaligned..misaligned + frozen.len()]).unwrap_err(),
+        LoadError::MisalignedMemory { align }
+    );
+    // while `load` falls back to a copy
+    assert!(matches!(
+        FrozenHashMap::load(&buffer[misaligned..misaligned + frozen.len()])?.memory,
+        Memory::Owned(_)
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn as_contiguous_pairs() -> Result<()> {
+    let map: HashMap<u32, u32> = [(1, 10), (2, 20), (3, 30)].into_iter().collect();
+
+    let mut frozen = FrozenHashMap::construct(&map);
+    assert!(frozen.as_contiguous_pairs::<u32, u32>().is_none());
+
+    // fill in the one empty bucket by hand
+    let buckets = frozen.hashmap.table.table.bucket_mask + 1;
+    assert_eq!(buckets, 4);
+    let size = frozen.table_layout.size;
+    let ctrl_offset = frozen.memory.len() - buckets - frozen_hashbrown::Group::WIDTH;
+    let memory = frozen.memory.to_mut(frozen.table_layout.ctrl_align);
+    let empty = (0..buckets)
+        .find(|i| memory[ctrl_offset + i] & 0x80 != 0)
+        .context("No empty bucket")?;
+    memory[ctrl_offset + empty] = 0x01;
+    let bucket = ctrl_offset - (empty + 1) * size;
+    memory[bucket..bucket + 4].copy_from_slice(&4u32.to_ne_bytes());
+    memory[bucket + 4..bucket + 8].copy_from_slice(&40u32.to_ne_bytes());
+    frozen.hashmap.table.table.items += 1;
+
+    let mut pairs = frozen
+        .as_contiguous_pairs::<u32, u32>()
+        .context("Not contiguous")?
+        .to_vec();
+    pairs.sort();
+    assert_eq!(pairs, [(1, 10), (2, 20), (3, 30), (4, 40)]);
+    assert!(frozen.as_contiguous_pairs::<u64, u64>().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn capacity() -> Result<()> {
+    let map: HashMap<u32, u32> = [(1, 10), (2, 20), (3, 30)].into_iter().collect();
+    let frozen = FrozenHashMap::construct(&map);
+    assert_eq!(frozen.capacity(), map.capacity());
+    assert_eq!(frozen.bucket_capacity(), 3);
+
+    let mut map: HashMap<u32, u32> = (0..100).map(|v| (v, v)).collect();
+    let frozen = FrozenHashMap::construct(&map);
+    assert_eq!(frozen.capacity(), map.capacity());
+    assert_eq!(frozen.bucket_capacity(), 112);
+
+    // removing leaves tombstones or frees slots, never exceeding the load factor
+    map.retain(|k, _| k % 2 == 0);
+    let frozen = FrozenHashMap::construct(&map);
+    assert_eq!(frozen.capacity(), map.capacity());
+    assert!(frozen.capacity() <= frozen.bucket_capacity());
+
+    Ok(())
+}
+
+#[test]
+fn unfreeze_shrink_to_fit() -> Result<()> {
+    let mut map: HashMap<u64, u32> = (0..1000).map(|v| (v, v as u32)).collect();
+    map.retain(|k, _| *k < 5);
+    map.shrink_to_fit();
+    let snapshot = format!("{map:?}");
+
+    let frozen = FrozenHashMap::construct(&map);
+    std::mem::drop(map);
+    let bucket_mask = frozen.hashmap.table.table.bucket_mask;
+    assert_eq!(bucket_mask, 7);
+    let (layout, _) = frozen
+        .table_layout
+        .calculate_layout_for(bucket_mask + 1)
+        .context("Bad layout")?;
+    assert_eq!(frozen.memory.len(), layout.size());
+    let memory_len = frozen.memory.len();
+    let frozen: Vec<u8> = frozen.store();
+
+    let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    assert_eq!(unfrozen.hashmap.table.table.bucket_mask, bucket_mask);
+    assert_eq!(unfrozen.memory.len(), memory_len);
+    assert_eq!(unfrozen.raw_iter().context("No iter")?.count(), 5);
+    let unfrozen = unfrozen
+        .reconstruct::<u64, u32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+    assert_eq!(unfrozen.get(&4), Some(&4));
+
+    Ok(())
+}
+
+#[test]
+fn store_with_checksum() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<char, i32> = ('a'..='z').zip(0..).collect();
+    let snapshot = format!("{map:?}");
+    let frozen = FrozenHashMap::construct(&map);
+    let memory_len = frozen.memory.len();
+    let stored = frozen.store_with_checksum();
+    assert_eq!(stored.len(), frozen.store().len());
+
+    let mut unfrozen = FrozenHashMap::load(&stored)?;
+    let unfrozen = unfrozen
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    // a corrupted control byte, the last byte before the trailing mirror
+    let ctrl = stored.len() - 1 - frozen_hashbrown::Group::WIDTH;
+    let mut corrupt = stored.clone();
+    corrupt[ctrl] ^= 0x80;
+    assert!(matches!(
+        FrozenHashMap::load(&corrupt),
+        Err(LoadError::ChecksumMismatch { .. })
+    ));
+    assert!(matches!(
+        FrozenHashMap::load_checked(&corrupt),
+        Err(LoadError::ChecksumMismatch { .. })
+    ));
+    assert!(FrozenHashMap::load_validated(&corrupt).is_err());
+
+    // the checksum covers the header too
+    let mut corrupt = stored.clone();
+    corrupt[stored.len() - memory_len - 24] ^= 1;
+    assert!(matches!(
+        FrozenHashMap::load(&corrupt),
+        Err(LoadError::ChecksumMismatch { .. })
+    ));
+
+    // without a checksum, the same corruption goes unnoticed by `load`
+    let mut corrupt = frozen.store();
+    corrupt[ctrl] ^= 0x80;
+    assert!(FrozenHashMap::load(&corrupt).is_ok());
+
+    Ok(())
+}
+
+#[test]
+fn load_validates_table() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<char, i32> = ('a'..='z').zip(0..).collect();
+    let frozen = FrozenHashMap::construct(&map).store();
+    let buckets = FrozenHashMap::peek_header(&frozen)
+        .context("No header")?
+        .buckets
+        .context("No buckets")?;
+
+    // bucket_mask, then items, in the 8-byte fields after the platform
+    let mut corrupt = frozen.clone();
+    corrupt[40..48].copy_from_slice(&(buckets as u64 - 2).to_ne_bytes());
+    assert_eq!(
+        FrozenHashMap::load(&corrupt).unwrap_err(),
+        LoadError::NonPowerOfTwoBuckets
+    );
+
+    let mut corrupt = frozen.clone();
+    corrupt[56..64].copy_from_slice(&(buckets as u64 + 1).to_ne_bytes());
+    assert_eq!(
+        FrozenHashMap::load(&corrupt).unwrap_err(),
+        LoadError::ItemsExceedBuckets
+    );
+
+    let mut corrupt = frozen.clone();
+    corrupt[40..48].copy_from_slice(&(buckets as u64 * 2 - 1).to_ne_bytes());
+    assert!(matches!(
+        FrozenHashMap::load(&corrupt),
+        Err(LoadError::MemorySizeMismatch { .. })
+    ));
+
+    let mut corrupt = frozen.clone();
+    corrupt[0] = b'X';
+    assert_eq!(
+        FrozenHashMap::load(&corrupt).unwrap_err(),
+        LoadError::BadMagic
+    );
+
+    let mut corrupt = frozen.clone();
+    corrupt.pop();
+    assert_eq!(
+        FrozenHashMap::load(&corrupt).unwrap_err(),
+        LoadError::TruncatedBlob
+    );
+    Ok(())
+}
+
+#[test]
+fn memory_aligned_for_group_loads() -> Result<()> {
+    use frozen_hashbrown::{Group, Memory};
+
+    let map: HashMap<u8, u8> = (0..100).map(|v| (v, v)).collect();
+    let mut frozen = FrozenHashMap::construct(&map);
+    let len = frozen.memory.len();
+    // an odd address, which no group load can start at
+    let mut bytes = vec![0u8; len + 1];
+    let offset = 1 - bytes.as_ptr() as usize % 2;
+    bytes[offset..offset + len].copy_from_slice(&frozen.memory);
+    frozen.memory = Memory::Borrowed(&bytes[offset..offset + len]);
+    frozen.table_layout.ctrl_align = 1;
+    assert!(frozen.raw_iter().is_none());
+    assert!(frozen.reconstruct::<u8, u8>().is_none());
+
+    let mut owned = frozen.into_owned();
+    assert_eq!(owned.memory.as_ptr() as usize % Group::WIDTH, 0);
+    let unfrozen = owned
+        .reconstruct::<u8, u8>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(unfrozen, &map);
+
+    Ok(())
+}
+
+#[test]
+fn load_rejects_small_ctrl_align() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<u8, u8> = (0..100).map(|v| (v, v)).collect();
+    let frozen = FrozenHashMap::construct(&map).store();
+    // ctrl_align, patched to 1, in a buffer where the memory is misaligned
+    let mut corrupt = vec![0u8];
+    corrupt.extend_from_slice(&frozen);
+    corrupt[1 + 32..1 + 40].copy_from_slice(&1usize.to_ne_bytes());
+    let corrupt = &corrupt[1..];
+    assert_eq!(
+        FrozenHashMap::load(corrupt).unwrap_err(),
+        LoadError::InvalidTableLayout
+    );
+    assert_eq!(
+        FrozenHashMap::load_checked(corrupt).unwrap_err(),
+        LoadError::InvalidTableLayout
+    );
+    assert_eq!(
+        FrozenHashMap::load_validated(corrupt).unwrap_err(),
+        LoadError::InvalidTableLayout
+    );
+
+    // ctrl_align in a minimal blob
+    let mut minimal = FrozenHashMap::construct(&map).store_minimal();
+    minimal[28..36].copy_from_slice(&1u64.to_le_bytes());
+    assert!(FrozenHashMap::load_minimal(&minimal).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn load_rejects_overflowing_bucket_mask() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<char, i32> = ('a'..='z').zip(0..).collect();
+    let mut corrupt = FrozenHashMap::construct(&map).store();
+    // bucket_mask, where `bucket_mask + 1` would overflow
+    corrupt[40..48].copy_from_slice(&u64::MAX.to_ne_bytes());
+    assert_eq!(
+        FrozenHashMap::load(&corrupt).unwrap_err(),
+        LoadError::NonPowerOfTwoBuckets
+    );
+    assert_eq!(
+        FrozenHashMap::load_checked(&corrupt).unwrap_err(),
+        LoadError::NonPowerOfTwoBuckets
+    );
+    assert!(FrozenHashMap::load_validated(&corrupt).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn peek_header_truncated() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<u32, u64> = (0..100).map(|v| (v, v as u64)).collect();
+    let frozen = FrozenHashMap::construct(&map);
+    let memory_len = frozen.memory.len();
+    let frozen: Vec<u8> = frozen.store();
+    let header_len = frozen.len() - memory_len;
+
+    let info = FrozenHashMap::peek_header(&frozen).context("No header")?;
+    assert_eq!(info.items, 100);
+    assert_eq!(info.buckets, Some(128));
+    assert_eq!(info.stored_memory_len, Some(memory_len));
+    assert_eq!(info.expected_memory_len, Some(memory_len));
+    assert_eq!(info.available_memory_len, memory_len);
+
+    let truncated = &frozen[..header_len + 100];
+    assert_eq!(
+        FrozenHashMap::load(truncated).unwrap_err(),
+        LoadError::TruncatedBlob
+    );
+    let info = FrozenHashMap::peek_header(truncated).context("No header")?;
+    assert_eq!(info.items, 100);
+    assert_eq!(info.expected_memory_len, Some(memory_len));
+    assert_eq!(info.available_memory_len, 100);
+
+    // without the length prefix
+    let info = FrozenHashMap::peek_header(&frozen[..header_len - 8]).context("No header")?;
+    assert_eq!(info.stored_memory_len, None);
+    assert_eq!(info.expected_memory_len, Some(memory_len));
+    assert!(FrozenHashMap::peek_header(&frozen[..header_len - 9]).is_none());
+
+    // bucket_mask, where `bucket_mask + 1` would overflow
+    let mut corrupt = frozen.clone();
+    corrupt[40..48].copy_from_slice(&u64::MAX.to_ne_bytes());
+    let info = FrozenHashMap::peek_header(&corrupt).context("No header")?;
+    assert_eq!(info.buckets, None);
+    assert_eq!(info.expected_memory_len, None);
+
+    Ok(())
+}
+
+#[test]
+fn raw_iter_clone() -> Result<()> {
+    let map: HashMap<i32, i32> = (0..100).map(|v| (v, v)).collect();
+    let frozen = FrozenHashMap::construct(&map);
+
+    let mut iter = frozen.raw_iter().context("No iter")?;
+    assert_eq!(iter.remaining(), 100);
+    iter.by_ref().take(40).for_each(drop);
+    assert_eq!(iter.remaining(), 60);
+    let fork = iter.clone();
+    let rest: Vec<*const u8> = iter.collect();
+    assert_eq!(rest.len(), 60);
+    assert_eq!(rest, fork.collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[test]
+fn dump_text() -> Result<()> {
+    let map: HashMap<char, &str> = [('a', "apple"), ('b', "banana")].into_iter().collect();
+    let frozen = FrozenHashMap::construct(&map);
+
+    let mut text = Vec::new();
+    frozen.dump_text::<char, &str>(&mut text)?;
+    let mut lines: Vec<&str> = std::str::from_utf8(&text)?.lines().collect();
+    lines.sort();
+    assert_eq!(lines, ["'a'\t\"apple\"", "'b'\t\"banana\""]);
+
+    assert!(frozen.dump_text::<u8, u8>(&mut Vec::new()).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn reconstruct_checked_type_fingerprint() -> Result<()> {
+    use frozen_hashbrown::TypeMismatch;
+
+    let map: HashMap<u32, u32> = [(1, 10), (2, 20)].into_iter().collect();
+    let frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+
+    let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    assert!(matches!(
+        unfrozen.reconstruct_checked::<(u16, u16), u32>(),
+        Err(TypeMismatch::Fingerprint { .. })
+    ));
+    let unfrozen = unfrozen
+        .reconstruct_checked::<u32, u32>()?
+        .context("Failed to reconstruct")?;
+    assert_eq!(unfrozen.get(&2), Some(&20));
+
+    Ok(())
+}
+
+#[test]
+fn can_reconstruct() -> Result<()> {
+    use frozen_hashbrown::TypeMismatch;
+
+    #[repr(align(32))]
+    struct Aligned {
+        _bytes: [u8; 32],
+    }
+
+    let map: HashMap<[u8; 32], ()> = [([1; 32], ()), ([2; 32], ())].into_iter().collect();
+    let mut frozen = FrozenHashMap::construct(&map);
+    std::mem::drop(map);
+    assert_eq!(frozen.can_reconstruct::<[u8; 32], ()>(), Ok(()));
+
+    // candidate types, as a tool reading debug info would try them
+    assert_eq!(
+        frozen.can_reconstruct::<u64, u64>(),
+        Err(TypeMismatch::Size {
+            expected: 32,
+            found: 16
+        })
+    );
+    assert_eq!(
+        frozen.can_reconstruct::<Aligned, ()>(),
+        Err(TypeMismatch::Align {
+            max: frozen.table_layout.ctrl_align,
+            found: 32
+        })
+    );
+    assert!(frozen.reconstruct::<u64, u64>().is_none());
+    assert!(frozen.reconstruct::<Aligned, ()>().is_none());
+    assert!(frozen.iter::<u64, u64>().is_none());
+    assert!(frozen.get::<u64, u64>(&1).is_none());
+
+    let unfrozen = frozen
+        .reconstruct::<[u8; 32], ()>()
+        .context("Failed to reconstruct")?;
+    assert!(unfrozen.contains_key(&[2; 32]));
+
+    Ok(())
+}
+
+#[test]
+fn load_validated() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<u32, u128> = (0..100).map(|v| (v, v as u128)).collect();
+    let snapshot = format!("{map:?}");
+    let frozen = FrozenHashMap::construct(&map);
+    let blob = frozen.store();
+
+    let mut unfrozen = FrozenHashMap::load_validated(&blob)?;
+    assert_eq!(
+        unfrozen.memory.as_ptr() as usize % unfrozen.table_layout.ctrl_align,
+        0
+    );
+    let unfrozen = unfrozen
+        .reconstruct::<u32, u128>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    let mut bad = blob.clone();
+    bad[0] = b'X';
+    assert_eq!(
+        FrozenHashMap::load_validated(&bad).unwrap_err(),
+        LoadError::BadMagic
+    );
+    assert_eq!(
+        FrozenHashMap::load_validated(&blob[..blob.len() - 1]).unwrap_err(),
+        LoadError::TruncatedBlob
+    );
+    let mut bad = blob.clone();
+    bad.push(0);
+    assert_eq!(
+        FrozenHashMap::load_validated(&bad).unwrap_err(),
+        LoadError::TrailingBytes
+    );
+
+    let mut bad = frozen.clone();
+    bad.hashmap.table.table.bucket_mask -= 1;
+    assert_eq!(
+        FrozenHashMap::load_validated(&bad.store()).unwrap_err(),
+        LoadError::NonPowerOfTwoBuckets
+    );
+    let mut bad = frozen.clone();
+    bad.hashmap.table.table.items += 1;
+    assert_eq!(
+        FrozenHashMap::load_validated(&bad.store()).unwrap_err(),
+        LoadError::ItemsMismatch {
+            items: 101,
+            full: 100
+        }
+    );
+    let mut bad = frozen.clone();
+    bad.hashmap.table.table.bucket_mask = 255;
+    assert!(matches!(
+        FrozenHashMap::load_validated(&bad.store()).unwrap_err(),
+        LoadError::MemorySizeMismatch { .. }
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn unfreeze_cloning_strings() -> Result<()> {
+    use frozen_hashbrown::FrozenOwnedHashMap;
+
+    let map: HashMap<String, String> = (0..100)
+        .map(|v| (format!("key-{v}"), "value".repeat(v)))
+        .collect();
+    let expected = map.clone();
+
+    let frozen = FrozenHashMap::construct_cloning(&map);
+    std::mem::drop(map);
+    let frozen: Vec<u8> = frozen.store_with(|k, v, out| {
+        out.extend_from_slice(&(k.len() as u32).to_le_bytes());
+        out.extend_from_slice(k.as_bytes());
+        out.extend_from_slice(v.as_bytes());
+    });
+
+    let unfrozen = FrozenOwnedHashMap::load_with(&frozen, |bytes| {
+        let len = u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?) as usize;
+        let key = String::from_utf8(bytes.get(4..4 + len)?.to_vec()).ok()?;
+        let value = String::from_utf8(bytes.get(4 + len..)?.to_vec()).ok()?;
+        Some((key, value))
+    })
+    .context("Failed to load")?;
+    assert!(FrozenOwnedHashMap::<String, String>::load_with(&frozen, |_| None).is_none());
+
+    let unfrozen = unfrozen.into_hashmap();
+    assert_eq!(unfrozen, expected);
+    assert_eq!(
+        unfrozen.get("key-3").map(String::as_str),
+        Some("valuevaluevalue")
+    );
+
+    Ok(())
+}
+
+#[test]
+fn unfreeze_into_hashmap() -> Result<()> {
+    let map: HashMap<u32, u64> = (0..100).map(|v| (v, v as u64 * 3)).collect();
+    let snapshot = format!("{map:?}");
+
+    let frozen = FrozenHashMap::construct(&map);
+    std::mem::drop(map);
+    let frozen: Vec<u8> = frozen.store();
+    let unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    assert!(unfrozen.can_reconstruct::<u32, u32>().is_err());
+    assert!(unfrozen.into_hashmap::<u32, u32>().is_none());
+    assert!(unfrozen.into_hashmap::<u64, u64>().is_some());
+
+    // a growth_left the table has no room for
+    for growth_left in [1u64, 1 << 20, u64::MAX] {
+        let mut corrupt = frozen.clone();
+        let patched = u64::from_ne_bytes(corrupt[48..56].try_into()?).saturating_add(growth_left);
+        corrupt[48..56].copy_from_slice(&patched.to_ne_bytes());
+        let corrupt = FrozenHashMap::load(&corrupt).context("Failed to load")?;
+        assert!(corrupt.into_hashmap::<u32, u64>().is_none());
+    }
+
+    let mut owned = unfrozen
+        .into_hashmap::<u32, u64>()
+        .context("Failed to reconstruct")?;
+    std::mem::drop(unfrozen);
+    std::mem::drop(frozen);
+    assert_eq!(snapshot, format!("{owned:?}"));
+
+    // growing frees the copied allocation
+    owned.extend((100..1000).map(|v| (v, v as u64 * 3)));
+    owned.remove(&7);
+    assert_eq!(owned.len(), 999);
+    assert_eq!(owned.get(&500), Some(&1500));
+    assert_eq!(owned.get(&7), None);
+
+    Ok(())
+}
+
+#[test]
+fn freeze_thaw() -> Result<()> {
+    let map: HashMap<char, i32> = [('a', 1), ('b', 2), ('c', 3)].into_iter().collect();
+    let snapshot = format!("{map:?}");
+
+    let frozen: Vec<u8> = FrozenHashMap::freeze(&map).store();
+    let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    let unfrozen = unfrozen.thaw::<char, i32>().context("Failed to thaw")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    Ok(())
+}
+
+#[test]
+fn iter_blobs() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let mut archive = Vec::new();
+    for n in [0, 1, 10, 100, 1000] {
+        let map: HashMap<u32, u32> = (0..n).map(|v| (v, v)).collect();
+        archive.extend(FrozenHashMap::construct(&map).store());
+    }
+
+    let infos: Vec<_> = FrozenHashMap::iter_blobs(archive.as_slice()).collect::<Result<_, _>>()?;
+    assert_eq!(
+        infos.iter().map(|info| info.items).collect::<Vec<_>>(),
+        [0, 1, 10, 100, 1000]
+    );
+    assert_eq!(infos[0].memory_len, 0);
+    assert!(infos.iter().all(|info| info.buckets.is_power_of_two()));
+
+    let truncated = &archive[..archive.len() - 1];
+    let results: Vec<_> = FrozenHashMap::iter_blobs(truncated).collect();
+    assert_eq!(results.len(), 5);
+    assert_eq!(results[4], Err(LoadError::TruncatedBlob));
+
+    Ok(())
+}
+
+#[test]
+fn iter_blobs_rejects_huge_hasher_len() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<u32, u32> = (0..10).map(|v| (v, v)).collect();
+    let mut archive = FrozenHashMap::construct(&map).store();
+    // the hasher length is the last field before the hash_builder
+    archive[64..72].copy_from_slice(&(1u64 << 46).to_ne_bytes());
+
+    let results: Vec<_> = FrozenHashMap::iter_blobs(archive.as_slice()).collect();
+    assert_eq!(results, [Err(LoadError::HasherTooLarge(1 << 46))]);
+    assert_eq!(
+        FrozenHashMap::load(&archive).unwrap_err(),
+        LoadError::HasherTooLarge(1 << 46)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn load_checked_abi_signature() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<char, i32> = [('a', 1), ('b', 2)].into_iter().collect();
+    let mut frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+
+    let mut unfrozen = FrozenHashMap::load_checked(&frozen)?;
+    let unfrozen = unfrozen
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(unfrozen.get(&'b'), Some(&2));
+
+    // the signature follows the magic, the format version, the producer version
+    // and the platform
+    let signature = FrozenHashMap::abi_signature();
+    assert_eq!(frozen[16..24], signature.to_ne_bytes());
+    frozen[16..24].copy_from_slice(&(signature ^ 1).to_ne_bytes());
+    assert_eq!(
+        FrozenHashMap::load_checked(&frozen).unwrap_err(),
+        LoadError::AbiMismatch {
+            expected: signature,
+            found: signature ^ 1
+        }
+    );
+
+    Ok(())
+}
+
+#[test]
+fn reconstruct_borrowed() -> Result<()> {
+    use frozen_hashbrown::{AlignedBuffer, TableLayout};
+
+    let map: HashMap<char, i32> = [('a', 1), ('b', 2), ('c', 3), ('d', 4)]
+        .into_iter()
+        .collect();
+    let snapshot = format!("{map:?}");
+
+    let frozen = FrozenHashMap::construct(&map);
+    let table = &frozen.hashmap.table.table;
+    let unfrozen = unsafe {
+        FrozenHashMap::reconstruct_borrowed::<char, i32>(
+            &frozen.memory,
+            frozen.table_layout,
+            frozen.hashmap.hash_builder.clone(),
+            table.bucket_mask,
+            table.items,
+            table.growth_left,
+        )
+    }
+    .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{:?}", *unfrozen));
+    assert_eq!(unfrozen.get(&'c'), Some(&3));
+
+    // ctrl must be aligned
+    let mut padded = vec![0u8];
+    padded.extend_from_slice(&frozen.memory);
+    let padded = AlignedBuffer::new(&padded, frozen.table_layout.ctrl_align);
+    assert!(unsafe {
+        FrozenHashMap::reconstruct_borrowed::<char, i32>(
+            &padded[1..],
+            frozen.table_layout,
+            frozen.hashmap.hash_builder.clone(),
+            table.bucket_mask,
+            table.items,
+            table.growth_left,
+        )
+    }
+    .is_none());
+
+    // so must the size
+    assert!(unsafe {
+        FrozenHashMap::reconstruct_borrowed::<char, i32>(
+            &frozen.memory[1..],
+            frozen.table_layout,
+            frozen.hashmap.hash_builder.clone(),
+            table.bucket_mask,
+            table.items,
+            table.growth_left,
+        )
+    }
+    .is_none());
+
+    // a table layout that is invalid, or not the one of `(K, V)`, is refused
+    // instead of panicking
+    let borrowed = |table_layout| unsafe {
+        FrozenHashMap::reconstruct_borrowed::<char, i32>(
+            &frozen.memory,
+            table_layout,
+            frozen.hashmap.hash_builder.clone(),
+            table.bucket_mask,
+            table.items,
+            table.growth_left,
+        )
+        .is_none()
+    };
+    for ctrl_align in [0, 1, 3] {
+        assert!(borrowed(TableLayout {
+            ctrl_align,
+            ..frozen.table_layout
+        }));
+    }
+    assert!(borrowed(TableLayout {
+        size: 4,
+        ..frozen.table_layout
+    }));
+
+    Ok(())
+}
+
+#[test]
+fn length_prefix_le_u64() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<u32, u32> = (0..100).map(|v| (v, v)).collect();
+    let frozen = FrozenHashMap::construct(&map);
+    let memory_len = frozen.memory.len();
+    let mut frozen: Vec<u8> = frozen.store();
+
+    let cursor = frozen.len() - memory_len - 8;
+    assert_eq!(
+        frozen[cursor..cursor + 8],
+        (memory_len as u64).to_le_bytes()
+    );
+
+    frozen[cursor..cursor + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+    let expected = if cfg!(target_pointer_width = "64") {
+        LoadError::MemorySizeMismatch {
+            expected: memory_len,
+            found: u64::MAX as usize,
+        }
+    } else {
+        LoadError::MemoryTooLarge(u64::MAX)
+    };
+    assert_eq!(FrozenHashMap::load_checked(&frozen).unwrap_err(), expected);
+
+    Ok(())
+}
+
+#[test]
+fn canonical_iter() -> Result<()> {
+    // two maps with the same entries but different seeds and insertion orders
+    let a: HashMap<u32, char> = (0..26).map(|v| (v, (b'a' + v as u8) as char)).collect();
+    let b: HashMap<u32, char> = (0..26)
+        .rev()
+        .map(|v| (v, (b'a' + v as u8) as char))
+        .collect();
+
+    let a = FrozenHashMap::construct(&a);
+    let b = FrozenHashMap::construct(&b);
+    let a: Vec<_> = a
+        .canonical_iter::<u32, char>()
+        .context("Failed to iterate")?
+        .collect();
+    let b: Vec<_> = b
+        .canonical_iter::<u32, char>()
+        .context("Failed to iterate")?
+        .collect();
+    assert_eq!(a, b);
+    assert_eq!(a.first(), Some(&&(0, 'a')));
+    assert_eq!(a.last(), Some(&&(25, 'z')));
+
+    Ok(())
+}
+
+#[test]
+fn hash_and_eq_as() -> Result<()> {
+    use std::collections::HashSet;
+
+    let map: HashMap<u32, u32> = (0..100).map(|v| (v, v * 2)).collect();
+    let frozen = FrozenHashMap::construct(&map);
+    let stored: Vec<u8> = frozen.store();
+    let loaded = FrozenHashMap::load(&stored).context("Failed to load")?;
+    // ctrl points into different memory, but the bytes are the same
+    assert_eq!(frozen, loaded);
+
+    let mut cache = HashSet::new();
+    assert!(cache.insert(frozen.clone()));
+    assert!(!cache.insert(loaded.into_owned()));
+
+    // same entries, different seed
+    let other: HashMap<u32, u32> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    let other = FrozenHashMap::construct(&other);
+    assert_ne!(frozen, other);
+    assert_eq!(frozen.eq_as::<u32, u32, _>(&other), Some(true));
+
+    let different: HashMap<u32, u32> = (0..100).map(|v| (v, v * 3)).collect();
+    let different = FrozenHashMap::construct(&different);
+    assert_eq!(frozen.eq_as::<u32, u32, _>(&different), Some(false));
+    assert_eq!(frozen.eq_as::<u64, u64, _>(&different), None);
+
+    Ok(())
+}
+
+#[test]
+fn into_std_hashmap() -> Result<()> {
+    use frozen_hashbrown::{AlignedBuffer, Memory};
+
+    let map: HashMap<u32, String> = (0..100).map(|v| (v, v.to_string())).collect();
+    let snapshot = format!("{map:?}");
+
+    let frozen = FrozenHashMap::construct(&map);
+    // the strings now belong to the frozen copy of the table
+    std::mem::forget(map);
+    let mut owned =
+        unsafe { frozen.into_std_hashmap::<u32, String>() }.context("Failed to convert")?;
+    assert_eq!(snapshot, format!("{owned:?}"));
+
+    // grows, which frees the table that used to be `memory`
+    owned.extend((100..1000).map(|v| (v, v.to_string())));
+    owned.get_mut(&1).context("No entry")?.push('!');
+    assert_eq!(owned.remove(&2).as_deref(), Some("2"));
+    assert_eq!(owned.len(), 999);
+    assert_eq!(owned[&1], "1!");
+    assert_eq!(owned[&999], "999");
+    std::mem::drop(owned);
+
+    // from a blob, where `memory` is copied first if borrowed
+    let map: HashMap<u64, u64> = (0..100).map(|v| (v, v * 2)).collect();
+    let snapshot = format!("{map:?}");
+    let stored = FrozenHashMap::construct(&map).store();
+    let frozen = FrozenHashMap::load(&stored)?;
+    assert!(unsafe { frozen.clone().into_std_hashmap::<u32, u32>() }.is_none());
+    let mut owned =
+        unsafe { frozen.into_std_hashmap::<u64, u64>() }.context("Failed to convert")?;
+    std::mem::drop(stored);
+    assert_eq!(snapshot, format!("{owned:?}"));
+    owned.insert(1000, 2000);
+    assert_eq!(owned.len(), 101);
+
+    // a buffer allocated with another alignment is copied before it is handed over
+    let mut frozen = FrozenHashMap::construct(&map);
+    frozen.memory = Memory::Owned(AlignedBuffer::new(&frozen.memory, 1));
+    let owned = unsafe { frozen.into_std_hashmap::<u64, u64>() }.context("Failed to convert")?;
+    assert_eq!(snapshot, format!("{owned:?}"));
+    let mut frozen = FrozenHashMap::construct(&map);
+    let memory_len = frozen.memory.len();
+    frozen.memory = Memory::Owned(AlignedBuffer::new(&frozen.memory[..memory_len - 1], 16));
+    assert!(unsafe { frozen.into_std_hashmap::<u64, u64>() }.is_none());
+
+    let empty: HashMap<u64, u64> = HashMap::new();
+    let mut owned = unsafe { FrozenHashMap::construct(&empty).into_std_hashmap::<u64, u64>() }
+        .context("Failed to convert")?;
+    owned.insert(1, 2);
+    assert_eq!(owned[&1], 2);
+
+    Ok(())
+}
+
+#[test]
+fn into_hashmap_with_capacity() -> Result<()> {
+    let map: HashMap<String, Vec<u32>> = (0..10).map(|v| (v.to_string(), vec![v])).collect();
+
+    // the entries are cloned while `map` still owns the strings they point to
+    let frozen = FrozenHashMap::construct(&map);
+    let mut owned = frozen
+        .into_hashmap_with_capacity::<String, Vec<u32>>(100)
+        .context("Failed to rebuild")?;
+    assert_eq!(owned, map);
+    assert!(owned.capacity() >= 110);
+    let capacity = owned.capacity();
+    owned.extend((10..110).map(|v| (v.to_string(), vec![v])));
+    assert_eq!(owned.capacity(), capacity);
+
+    let empty: HashMap<u32, u32> = HashMap::new();
+    let frozen = FrozenHashMap::construct(&empty);
+    let owned = frozen
+        .into_hashmap_with_capacity::<u32, u32>(8)
+        .context("Failed to rebuild")?;
+    assert!(owned.is_empty());
+    assert!(owned.capacity() >= 8);
+    // the types are checked even without entries
+    assert!(frozen
+        .into_hashmap_with_capacity::<u64, String>(8)
+        .is_none());
+    assert!(frozen.into_hashmap::<u64, u64>().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn unfreeze_single_entry() -> Result<()> {
+    let map = HashMap::from([(42i32, "x")]);
+
+    let frozen = FrozenHashMap::construct(&map);
+    // the smallest allocated table
+    assert_eq!(frozen.hashmap.table.table.bucket_mask, 3);
+    assert_eq!(frozen.bucket_capacity(), 3);
+    let frozen: Vec<u8> = frozen.store();
+
+    let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    assert_eq!(unfrozen.len(), 1);
+    assert_eq!(
+        unfrozen
+            .raw_iter()
+            .context("Failed to iterate")?
+            .map(|ptr| unsafe { *(ptr as *const (i32, &str)) })
+            .collect::<Vec<_>>(),
+        [(42, "x")]
+    );
+    let unfrozen = unfrozen
+        .reconstruct::<i32, &str>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(unfrozen.len(), 1);
+    assert_eq!(unfrozen.get(&42), Some(&"x"));
+    assert_eq!(unfrozen.get(&43), None);
+    assert_eq!(unfrozen.iter().collect::<Vec<_>>(), [(&42, &"x")]);
+
+    Ok(())
+}
+
+#[test]
+fn diagnostic() -> Result<()> {
+    let map: HashMap<u32, u32> = (0..100).map(|v| (v, v)).collect();
+    let frozen = FrozenHashMap::construct(&map);
+
+    let report = frozen.diagnostic();
+    assert!(report.contains("bucket_mask: 127\n"));
+    assert!(report.contains("buckets: Some(128)\n"));
+    assert!(report.contains("items: 100\n"));
+    assert!(report.contains(&format!("memory.len(): {}\n", frozen.memory.len())));
+    assert!(report.contains("ctrl bytes available: 128\n"));
+    let head = report
+        .lines()
+        .find_map(|line| line.strip_prefix("ctrl head: "))
+        .context("No ctrl head")?;
+    assert_eq!(head.split(' ').count(), 16);
+
+    let empty: HashMap<u32, u32> = HashMap::new();
+    let report = FrozenHashMap::construct(&empty).diagnostic();
+    assert!(report.contains("empty_singleton: true\n"));
+    assert!(report.contains("(layout, ctrl_offset): None\n"));
+
+    Ok(())
+}
+
+#[test]
+fn unfreeze_empty_singleton_and_allocated_empty() -> Result<()> {
+    // neither allocates
+    for map in [HashMap::<char, i32>::new(), HashMap::with_capacity(0)] {
+        let frozen = FrozenHashMap::construct(&map);
+        assert_eq!(frozen.hashmap.table.table.bucket_mask, 0);
+        assert!(frozen.memory.is_empty());
+        let frozen: Vec<u8> = frozen.store();
+
+        let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+        let unfrozen = unfrozen
+            .reconstruct::<char, i32>()
+            .context("Failed to reconstruct")?;
+        assert!(unfrozen.is_empty());
+        assert_eq!(unfrozen.capacity(), 0);
+        assert_eq!(unfrozen.get(&'a'), None);
+        assert_eq!(unfrozen.iter().count(), 0);
+        assert_eq!(format!("{unfrozen:?}"), "{}");
+    }
+
+    // keeps its allocation
+    let mut map: HashMap<char, i32> = HashMap::with_capacity(10);
+    let capacity = map.capacity();
+    map.insert('a', 1);
+    map.clear();
+    let frozen = FrozenHashMap::construct(&map);
+    assert_ne!(frozen.hashmap.table.table.bucket_mask, 0);
+    assert!(!frozen.memory.is_empty());
+    let frozen: Vec<u8> = frozen.store();
+
+    let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    let unfrozen = unfrozen
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert!(unfrozen.is_empty());
+    assert_eq!(unfrozen.capacity(), capacity);
+    assert_eq!(unfrozen.get(&'a'), None);
+    assert_eq!(format!("{unfrozen:?}"), "{}");
+
+    Ok(())
+}
+
+#[test]
+fn raw_iter_both_ends() -> Result<()> {
+    // bucket 0, whose control byte is mirrored, is full for some seeds
+    for _ in 0..20 {
+        let map: HashMap<u32, u32> = (0..13).map(|v| (v, v * 10)).collect();
+        let frozen = FrozenHashMap::construct(&map);
+        let raw_iter = frozen.raw_iter().context("No iter")?;
+        assert_eq!(raw_iter.len(), 13);
+
+        let forward: Vec<*const u8> = raw_iter.clone().collect();
+        let mut backward: Vec<*const u8> = raw_iter.clone().rev().collect();
+        backward.reverse();
+        assert_eq!(forward, backward);
+
+        // both ends meet in the middle without yielding a bucket twice
+        let mut raw_iter = raw_iter;
+        let mut alternating = Vec::new();
+        let mut front = Vec::new();
+        while let Some(ptr) = raw_iter.next() {
+            front.push(ptr);
+            assert_eq!(raw_iter.len(), 13 - front.len() - alternating.len());
+            if let Some(ptr) = raw_iter.next_back() {
+                alternating.push(ptr);
+            }
+        }
+        assert!(raw_iter.next_back().is_none());
+        alternating.reverse();
+        front.extend(alternating);
+        assert_eq!(forward, front);
+
+        let typed: Vec<(u32, u32)> = frozen
+            .iter::<u32, u32>()
+            .context("No iter")?
+            .rev()
+            .map(|(k, v)| (*k, *v))
+            .collect();
+        let mut expected: Vec<(u32, u32)> = map.iter().map(|(k, v)| (*k, *v)).collect();
+        expected.reverse();
+        assert_eq!(typed, expected);
+
+        let stored = frozen.store();
+        let owned = FrozenHashMap::load_owned(&stored)?
+            .into_raw_iter()
+            .context("No iter")?;
+        std::mem::drop(stored);
+        assert_eq!(owned.len(), 13);
+        // the iterator can be sent away along with the memory
+        let mut from_thread = std::thread::spawn(move || {
+            owned
+                .rev()
+                .map(|ptr| unsafe { *(ptr as *const (u32, u32)) })
+                .collect::<Vec<_>>()
+        })
+        .join()
+        .map_err(|_| anyhow::anyhow!("Thread panicked"))?;
+        from_thread.reverse();
+        let forward: Vec<(u32, u32)> = forward
+            .into_iter()
+            .map(|ptr| unsafe { *(ptr as *const (u32, u32)) })
+            .collect();
+        assert_eq!(forward, from_thread);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn copied_iter() -> Result<()> {
+    let map: HashMap<u64, f32> = (0..50).map(|v| (v, v as f32 / 2.0)).collect();
+
+    let frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+    let unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    let copied: HashMap<u64, f32> = unfrozen
+        .copied_iter::<u64, f32>()
+        .context("Failed to iterate")?
+        .collect();
+    std::mem::drop(unfrozen);
+    assert_eq!(copied, map);
+
+    let unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    assert!(unfrozen.copied_iter::<u32, f32>().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn reconstruct_recompute_growth() -> Result<()> {
+    let mut map: HashMap<u32, u32> = (0..100).map(|v| (v, v)).collect();
+    map.retain(|k, _| k % 3 != 0);
+    let snapshot = format!("{map:?}");
+
+    let mut frozen = FrozenHashMap::construct(&map);
+    let growth_left = frozen.hashmap.table.table.growth_left;
+    frozen.hashmap.table.table.growth_left = usize::MAX;
+
+    let unfrozen = frozen
+        .reconstruct_recompute_growth::<u32, u32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+    assert_eq!(frozen.hashmap.table.table.growth_left, growth_left);
+
+    // more items than the table can hold
+    frozen.hashmap.table.table.items = frozen.bucket_capacity() + 1;
+    assert!(frozen.reconstruct_recompute_growth::<u32, u32>().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn store_load_minimal() -> Result<()> {
+    let mut map: HashMap<u32, u32> = (0..100).map(|v| (v, v * 2)).collect();
+    map.retain(|k, _| k % 5 != 0);
+
+    let frozen = FrozenHashMap::construct(&map);
+    let minimal = frozen.store_minimal();
+    assert_eq!(minimal.len(), 36 + frozen.memory.len());
+    assert!(minimal.len() < frozen.store().len());
+
+    let unfrozen = FrozenHashMap::load_minimal(&minimal).context("Failed to load")?;
+    assert_eq!(unfrozen.len(), map.len());
+    assert_eq!(unfrozen.capacity(), frozen.capacity());
+    assert_eq!(unfrozen.type_fingerprint, None);
+    let copied: HashMap<u32, u32> = unfrozen
+        .copied_iter::<u32, u32>()
+        .context("Failed to iterate")?
+        .collect();
+    assert_eq!(copied, map);
+
+    assert!(FrozenHashMap::load_minimal(&minimal[..minimal.len() - 1]).is_none());
+    assert!(FrozenHashMap::load_minimal(&frozen.store()).is_none());
+
+    let empty: HashMap<u32, u32> = HashMap::new();
+    let minimal = FrozenHashMap::construct(&empty).store_minimal();
+    let unfrozen = FrozenHashMap::load_minimal(&minimal).context("Failed to load")?;
+    assert!(unfrozen.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn expected_memory_len() -> Result<()> {
+    let map: HashMap<u64, u16> = (0..100).map(|v| (v, v as u16)).collect();
+    let frozen = FrozenHashMap::construct(&map);
+    let table_layout = frozen.table_layout;
+
+    assert_eq!(
+        table_layout.expected_memory_len(frozen.hashmap.table.table.bucket_mask),
+        Some(frozen.memory.len())
+    );
+    // the empty singleton has no allocation
+    assert_eq!(table_layout.expected_memory_len(0), Some(0));
+    assert_eq!(table_layout.expected_memory_len(6), None);
+    assert_eq!(table_layout.expected_memory_len(usize::MAX), None);
+
+    Ok(())
+}
+
+#[test]
+fn repair_ctrl_mirror() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    // fewer buckets than a group, and more
+    for n in [1, 100] {
+        let map: HashMap<u32, u32> = (0..n).map(|v| (v, v)).collect();
+        let snapshot = format!("{map:?}");
+        let mut frozen = FrozenHashMap::construct(&map);
+        assert!(frozen.is_ctrl_mirror_consistent());
+
+        let ctrl_align = frozen.table_layout.ctrl_align;
+        let memory = frozen.memory.to_mut(ctrl_align);
+        let last = memory.len() - 1;
+        memory[last] ^= 0x80;
+        assert!(!frozen.is_ctrl_mirror_consistent());
+        assert_eq!(
+            FrozenHashMap::load_checked(&frozen.store()).unwrap_err(),
+            LoadError::CorruptCtrlMirror
+        );
+
+        frozen.repair_ctrl_mirror();
+        assert!(frozen.is_ctrl_mirror_consistent());
+        let stored: Vec<u8> = frozen.store();
+        let mut unfrozen = FrozenHashMap::load_checked(&stored)?;
+        let unfrozen = unfrozen
+            .reconstruct::<u32, u32>()
+            .context("Failed to reconstruct")?;
+        assert_eq!(snapshot, format!("{unfrozen:?}"));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn get_or_contains_then() -> Result<()> {
+    let map: HashMap<u64, u64> = (0..1000).map(|v| (v * 7, v)).collect();
+
+    let frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+    let unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    for (key, value) in map.iter() {
+        assert_eq!(unfrozen.get_or::<u64, u64>(key, &u64::MAX), value);
+    }
+    assert_eq!(unfrozen.get_or::<u64, u64>(&1, &u64::MAX), &u64::MAX);
+
+    let mut seen = None;
+    assert!(unfrozen.contains_then::<u64, u64, _>(&700, |v| seen = Some(*v)));
+    assert_eq!(seen, Some(100));
+    assert!(!unfrozen.contains_then::<u64, u64, _>(&701, |_| unreachable!()));
+
+    Ok(())
+}
+
+#[test]
+fn producer_version() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<u32, u32> = (0..10).map(|v| (v, v)).collect();
+    let mut frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+    assert_eq!(
+        FrozenHashMap::producer_version(&frozen).as_deref(),
+        Some(env!("CARGO_PKG_VERSION"))
+    );
+    assert!(FrozenHashMap::load_checked(&frozen).is_ok());
+
+    // stored by a future version
+    frozen[8] += 1;
+    assert!(matches!(
+        FrozenHashMap::load_checked(&frozen),
+        Err(LoadError::NewerProducer { .. })
+    ));
+    assert!(FrozenHashMap::load(&frozen).is_ok());
+
+    // still readable from a format version this crate does not support
+    frozen[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(
+        FrozenHashMap::load(&frozen).unwrap_err(),
+        LoadError::UnsupportedVersion(u32::MAX)
+    );
+    assert!(FrozenHashMap::producer_version(&frozen).is_some());
+    assert!(FrozenHashMap::producer_version(&frozen[..10]).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn from_memory_reader() -> Result<()> {
+    use core::alloc::Layout;
+    use frozen_hashbrown::{ExtractError, MemoryReader, TableLayout};
+
+    /// Reads the memory of this process, standing in for a coredump
+    struct SelfReader;
+
+    impl MemoryReader for SelfReader {
+        fn read_at(&self, addr: u64, buf: &mut [u8]) -> std::io::Result<()> {
+            unsafe {
+                core::ptr::copy_nonoverlapping(addr as *const u8, buf.as_mut_ptr(), buf.len())
+            };
+            Ok(())
+        }
+    }
+
+    struct FailingReader;
+
+    impl MemoryReader for FailingReader {
+        fn read_at(&self, _: u64, _: &mut [u8]) -> std::io::Result<()> {
+            Err(std::io::ErrorKind::UnexpectedEof.into())
+        }
+    }
+
+    let map: HashMap<char, i32> = [('a', 1), ('b', 2), ('c', 3), ('d', 4)]
+        .into_iter()
+        .collect();
+    let snapshot = format!("{map:?}");
+    let table_layout = TableLayout::new(Layout::new::<(char, i32)>());
+
+    let mut extracted =
+        FrozenHashMap::from_memory_reader(&SelfReader, &map as *const _ as u64, table_layout)?;
+    let unfrozen = extracted
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    let empty: HashMap<char, i32> = HashMap::new();
+    let mut extracted =
+        FrozenHashMap::from_memory_reader(&SelfReader, &empty as *const _ as u64, table_layout)?;
+    assert!(extracted.memory.is_empty());
+    assert!(extracted
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?
+        .is_empty());
+
+    let zeroes = [0u64; 8];
+    assert_eq!(
+        FrozenHashMap::from_memory_reader(&SelfReader, &zeroes as *const _ as u64, table_layout)
+            .unwrap_err(),
+        ExtractError::NullCtrl
+    );
+    assert_eq!(
+        FrozenHashMap::from_memory_reader(&FailingReader, 0, table_layout).unwrap_err(),
+        ExtractError::Io(std::io::ErrorKind::UnexpectedEof)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn from_parts() -> Result<()> {
+    use core::alloc::Layout;
+    use frozen_hashbrown::{ExtractError, TableLayout};
+
+    let map: HashMap<u64, u32> = (0..50).map(|v| (v, v as u32 * 3)).collect();
+    let snapshot = format!("{map:?}");
+    let table_layout = TableLayout::new(Layout::new::<(u64, u32)>());
+
+    // stands in for a coredump: the table allocation at a made-up address,
+    // and the fields of the map as read from debug info
+    let frozen = FrozenHashMap::construct(&map);
+    std::mem::drop(map);
+    let dump: Vec<u8> = frozen.memory.to_vec();
+    let table = &frozen.hashmap.table.table;
+    let (bucket_mask, items, growth_left) = (table.bucket_mask, table.items, table.growth_left);
+    let hash_builder = frozen.hashmap.hash_builder.clone();
+    let base: u64 = 0x7f00_0000_0000;
+    let ctrl_offset = dump.len() - (bucket_mask + 1) - frozen_hashbrown::Group::WIDTH;
+    let ctrl_addr = base + ctrl_offset as u64;
+    let read = |addr: u64, len: usize| {
+        let start = usize::try_from(addr.checked_sub(base)?).ok()?;
+        dump.get(start..start.checked_add(len)?).map(<[u8]>::to_vec)
+    };
+
+    let mut extracted = FrozenHashMap::from_parts(
+        table_layout,
+        hash_builder.clone(),
+        bucket_mask,
+        items,
+        growth_left,
+        ctrl_addr,
+        read,
+    )?;
+    assert_eq!(extracted.memory[..], dump[..]);
+    let unfrozen = extracted
+        .reconstruct::<u64, u32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    let from_parts =
+        |bucket_mask, items, ctrl_addr, read: &dyn Fn(u64, usize) -> Option<Vec<u8>>| {
+            FrozenHashMap::from_parts(
+                table_layout,
+                hash_builder.clone(),
+                bucket_mask,
+                items,
+                growth_left,
+                ctrl_addr,
+                read,
+            )
+            .unwrap_err()
+        };
+    assert_eq!(
+        from_parts(bucket_mask, items, ctrl_addr, &|_, _| None),
+        ExtractError::ReadFailed {
+            addr: base,
+            len: dump.len()
+        }
+    );
+    // a dump that ends early
+    assert!(matches!(
+        from_parts(bucket_mask, items, ctrl_addr, &|addr, len| read(
+            addr,
+            len - 1
+        )),
+        ExtractError::ReadFailed { .. }
+    ));
+    assert_eq!(
+        from_parts(bucket_mask - 1, items, ctrl_addr, &read),
+        ExtractError::NonPowerOfTwoBuckets
+    );
+    assert_eq!(
+        from_parts(usize::MAX, items, ctrl_addr, &read),
+        ExtractError::NonPowerOfTwoBuckets
+    );
+    // a corrupt bucket_mask is rejected before anything is read
+    assert!(matches!(
+        from_parts((1 << 40) - 1, items, u64::MAX, &|_, _| unreachable!()),
+        ExtractError::TableTooLarge(_)
+    ));
+    assert_eq!(
+        from_parts(bucket_mask, bucket_mask + 2, ctrl_addr, &read),
+        ExtractError::ItemsExceedBuckets
+    );
+    assert_eq!(
+        from_parts(bucket_mask, items, 0, &read),
+        ExtractError::NullCtrl
+    );
+    assert_eq!(
+        from_parts(bucket_mask, items, 16, &read),
+        ExtractError::AddressOverflow
+    );
+    let misaligned = TableLayout {
+        ctrl_align: 1,
+        ..table_layout
+    };
+    assert_eq!(
+        FrozenHashMap::from_parts(
+            misaligned,
+            hash_builder.clone(),
+            bucket_mask,
+            items,
+            growth_left,
+            ctrl_addr,
+            &read,
+        )
+        .unwrap_err(),
+        ExtractError::InvalidTableLayout
+    );
+
+    // the empty singleton has nothing to read
+    let mut extracted = FrozenHashMap::from_parts(
+        table_layout,
+        hash_builder,
+        0,
+        0,
+        0,
+        ctrl_addr,
+        |_, _| unreachable!(),
+    )?;
+    assert!(extracted
+        .reconstruct::<u64, u32>()
+        .context("Failed to reconstruct")?
+        .is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn portable_header() -> Result<()> {
+    use frozen_hashbrown::LoadError;
+
+    let map: HashMap<char, i32> = [('a', 1), ('b', 2), ('c', 3), ('d', 4)]
+        .into_iter()
+        .collect();
+    let snapshot = format!("{map:?}");
+    let frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+    let header_len = frozen.len() - FrozenHashMap::construct(&map).memory.len();
+
+    // as if stored on a platform of the other endianness
+    let mut swapped = frozen.clone();
+    swapped[11] ^= 1;
+    for field in swapped[16..header_len - 8].chunks_mut(8) {
+        field.reverse();
+    }
+    let mut unfrozen = FrozenHashMap::load(&swapped).context("Failed to load")?;
+    assert_eq!(
+        unfrozen,
+        FrozenHashMap::load(&frozen).context("Failed to load")?
+    );
+    let unfrozen = unfrozen
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+
+    let mut invalid = frozen.clone();
+    invalid[11] = 2;
+    assert!(FrozenHashMap::load(&invalid).is_err());
+    assert_eq!(
+        FrozenHashMap::load_checked(&invalid).unwrap_err(),
+        LoadError::InvalidEndianness(2)
+    );
+
+    // as if stored with a different Group::WIDTH
+    let mut other = frozen.clone();
+    other[13] *= 2;
+    assert!(FrozenHashMap::load(&other).is_err());
+    assert!(matches!(
+        FrozenHashMap::load_checked(&other),
+        Err(LoadError::PlatformMismatch {
+            what: "Group::WIDTH",
+            ..
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn typed_iter() -> Result<()> {
+    let map: HashMap<char, i32> = [('a', 1), ('b', 2), ('c', 3), ('d', 4)]
+        .into_iter()
+        .collect();
+
+    let frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+    let unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    let iter = unfrozen.iter::<char, i32>().context("Failed to iterate")?;
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+    // same order as the original map
+    assert_eq!(
+        iter.clone().collect::<Vec<_>>(),
+        map.iter().collect::<Vec<_>>()
+    );
+    for (k, v) in iter {
+        assert_eq!(map.get(k), Some(v));
+    }
+    assert!(unfrozen.iter::<char, u64>().is_none());
+
+    Ok(())
+}
+
+#[test]
+fn get_contains_key() -> Result<()> {
+    // from fewer buckets than a group, where probing reads the mirrored control
+    // bytes, to many groups, where the probe sequence wraps around
+    for n in [1u64, 3, 7, 14, 28, 100, 10_000] {
+        let map: HashMap<u64, String> = (0..n).map(|v| (v * 31, v.to_string())).collect();
+        let frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+        let unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+        for (key, value) in map.iter() {
+            assert_eq!(unfrozen.get::<u64, String>(key), Some(value));
+            assert!(unfrozen.contains_key::<u64, String>(key));
+        }
+        for key in (0..n).map(|v| v * 31 + 1) {
+            assert_eq!(unfrozen.get::<u64, String>(&key), None);
+            assert!(!unfrozen.contains_key::<u64, String>(&key));
+        }
+        // `(u32, String)` would have the size of a bucket, and could match key 0
+        assert_eq!(unfrozen.get::<u64, u64>(&0), None);
+    }
+
+    let empty: HashMap<u64, u64> = HashMap::new();
+    let frozen = FrozenHashMap::construct(&empty);
+    assert!(!frozen.contains_key::<u64, u64>(&0));
+
+    Ok(())
+}
+
+#[test]
+fn custom_hasher() -> Result<()> {
+    use std::{collections::hash_map::DefaultHasher, hash::BuildHasherDefault};
+
+    type Hasher = BuildHasherDefault<DefaultHasher>;
+
+    let map: HashMap<u32, char, Hasher> = (0..26).map(|v| (v, (b'a' + v as u8) as char)).collect();
+    let snapshot = format!("{map:?}");
+
+    let frozen = FrozenHashMap::construct_with_hasher(&map);
+    let memory_len = frozen.memory.len();
+    let stored = unsafe { frozen.store_with_hasher() };
+    // the zero-sized hasher only takes its length marker
+    let with_random_state = FrozenHashMap::construct(&HashMap::<u32, char>::from_iter(map.clone()));
+    assert_eq!(
+        stored.len() + 16 - memory_len,
+        with_random_state.store().len() - with_random_state.memory.len()
+    );
+
+    let mut unfrozen =
+        unsafe { FrozenHashMap::<Hasher>::load_with_hasher(&stored) }.context("Failed to load")?;
+    // the buckets hold `(u32, char)`, not `(u64, u64)`
+    assert!(unfrozen.reconstruct_with::<u64, u64>().is_none());
+    let unfrozen = unfrozen
+        .reconstruct_with::<u32, char>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(snapshot, format!("{unfrozen:?}"));
+    assert_eq!(unfrozen.get(&25), Some(&'z'));
+
+    // the stored hasher has a different size
+    assert!(
+        unsafe { FrozenHashMap::<Hasher>::load_with_hasher(&with_random_state.store()) }.is_err()
+    );
+    assert!(FrozenHashMap::load(&stored).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn unfreeze_empty() -> Result<()> {
+    let map = HashMap::<char, i32>::new();
+    let frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+    let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    let unfrozen = unfrozen
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(format!("{unfrozen:?}"), "{}");
+
+    // every element removed one by one, leaving tombstones behind
+    let mut map: HashMap<char, i32> = ('a'..='z').zip(0..).collect();
+    for c in 'a'..='z' {
+        map.remove(&c);
+    }
+    let frozen: Vec<u8> = FrozenHashMap::construct(&map).store();
+    let mut unfrozen = FrozenHashMap::load(&frozen).context("Failed to load")?;
+    assert_eq!(
+        unfrozen
+            .iter::<char, i32>()
+            .context("Failed to iterate")?
+            .count(),
+        0
+    );
+    let unfrozen = unfrozen
+        .reconstruct::<char, i32>()
+        .context("Failed to reconstruct")?;
+    assert_eq!(format!("{unfrozen:?}"), "{}");
+    assert_eq!(unfrozen.get(&'a'), None);
+
+    Ok(())
+}